@@ -0,0 +1,63 @@
+//! Generation speed and heap allocations of selections of nested ranges.
+//!
+//! Selections choose an entry before generating it, so the entries that are not chosen cost nothing
+//! and no memory is allocated while generating. Run with `cargo bench`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use grand::SeededRng;
+
+// Counts every allocation made by the program
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: u32 = 200_000;
+
+const EXPRESSIONS: [&str; 5] = [
+    "[1, 2, 3, 4, 5, 6]",
+    "[0..10, 20..30, 40..50, 60..70]",
+    "[(0..10|*2), (20..30|*3|!*2), (40..1000|*7), [1..2, 3..4]]",
+    "[(0..10)@2:70, (0..100)@2:25, (0..1000)@2:5]",
+    "[[[0..1, 2..3], [4..5, 6..7]], [[8..9, 10..11], [12..13, 14..15]]]",
+];
+
+fn main() {
+    println!("{:<70} {:>12} {:>12}", "expression", "ns/gen", "allocs/gen");
+    for expression in EXPRESSIONS {
+        let gex = grand::compile_raw(expression).expect("benchmark expressions are valid");
+        let mut rng = SeededRng::seed(0x5EED);
+
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(gex.generate_with(&mut rng));
+        }
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+        println!(
+            "{:<70} {:>12.1} {:>12.3}",
+            expression,
+            elapsed.as_nanos() as f64 / ITERATIONS as f64,
+            allocations as f64 / ITERATIONS as f64
+        );
+        assert_eq!(allocations, 0, "generating `{expression}` allocated memory");
+    }
+}
@@ -22,15 +22,286 @@
 //! characteristics:
 //! 
 //! - `0..100|*2` - The pipe (`|`) indicates a constraint and the asterisk (`*`) indicates a "multiple of" constraint.
-//!     In this case we are generating a random number between 0 and 100 that is a multiple of 2 (even).
+//!   In this case we are generating a random number between 0 and 100 that is a multiple of 2 (even).
 //! - `0..100|!*2` - We can also negate the constraint. This expression generates a random odd number between 0 and 100.
 //! - `0..100|*2,3,5` - This constraint indicates that the number must be a multiple of 2, 3 and 5.
 //! 
+//! Constraints that can't be satisfied by any number in the range are reported when compiling:
+//! 
+//! ```
+//! assert!(grand::compile_raw("0..10|*2|!*2").is_err()); // Every multiple of 2 is a multiple of 2
+//! assert!(grand::compile_raw("..|*6|!*3").is_err()); // Every multiple of 6 is a multiple of 3
+//! assert!(grand::compile_raw("3..3|*2|!*5").is_err()); // 3 is not a multiple of 2
+//! ```
+//! 
+//! ### Weighted selections
+//! 
+//! Selections (`[1, 2, 3]`) pick one of their entries. Every entry has the same chance unless they are given weights
+//! with `:`. Entries without a weight weigh 1:
+//! 
+//! - `[1:70, 2:25, 3:5]` - 1 is picked 70% of the time, 2 25% of the time and 3 5% of the time
+//! - `[(0..10):0.5, 100:0.25, 200]` - Weights can have decimals and the entries can be any expression
+//! 
+//! Weights are compared exactly and picking an entry takes the same time no matter how many entries there are.
+//! Negative weights and selections where every weight is 0 are reported when compiling:
+//! 
+//! ```
+//! let gex = grand::compile_raw("[1:1, 50:0, 2:3]").unwrap();
+//! assert_eq!(gex.max_number(), 2.into()); // 50 is never picked
+//! 
+//! assert!(grand::compile_raw("[1:-5, 2]").is_err());
+//! assert!(grand::compile_raw("[1:0, 2:0]").is_err());
+//! ```
+//! 
+//! `[0..1, 10..1000]` returns a number between 0 and 1 half of the time. To get a random number from all the entries
+//! together, add `|uniform` after the selection. Every entry is then weighted by the amount of values it can generate
+//! (`[1, 2..5:int]|uniform` picks 1 to 5 with the same chance) or, if some entries can't be counted, by the length between
+//! their bounds (`[0..1, 10..1000]|uniform` returns a number between 0 and 1 once every 991 times). Entries that overlap
+//! are counted twice, and `|uniform` can't be used together with weights:
+//! 
+//! ```
+//! let gex = grand::compile_raw("[0..1, 5]|uniform").unwrap();
+//! assert_eq!(gex.max_number(), 1.into()); // A single number has no length next to a range
+//! 
+//! assert!(grand::compile_raw("[1:2, 3]|uniform").is_err());
+//! ```
+//! 
+//! ### Arithmetic
+//! 
+//! The results of expressions can be combined with `+`, `-`, `*`, `/` and `%` (remainder). Multiplications, divisions
+//! and remainders go first, and ranges go before any operator (`0..10*2` is `(0..10)*2`):
+//! 
+//! - `(1..6)+(1..6)` - Adds two random numbers between 1 and 6
+//! - `(0..10)*0.5+3` - Generates a number between 0 and 10, halves it and adds 3
+//! - `-(0..10)` - Generates a number between -10 and 0
+//! 
+//! Operations between numbers are calculated while compiling. Dividing by a number that is always 0 is a compilation
+//! error, dividing by a range that generated 0 is an error when generating (see `try_generate()`).
+//! 
+//! ```
+//! let gex = grand::compile_raw("(1..6:int)+(1..6:int)").unwrap();
+//! assert_eq!(gex.min_number(), 2.into());
+//! assert_eq!(gex.max_number(), 12.into());
+//! 
+//! assert!(grand::compile_raw("(0..10)/(5-5)").is_err());
+//! ```
+//! 
+//! Note that `/*` always starts a comment, so the divisor has to come right after the slash (`8 / 2` or `8 /(2)`):
+//! 
+//! ```
+//! assert_eq!(grand::compile_raw("8 /(2)").unwrap().generate(), 4.into());
+//! assert!(grand::compile_raw("8 /* half */ 2").is_err()); // The comment is skipped: `8 2`
+//! ```
+//! 
+//! ### Dice
+//! 
+//! Dice are written like in tabletop games and can be used anywhere a number can:
+//! 
+//! - `3d6` - Rolls three six-sided dice and adds them. `d20` rolls a single die
+//! - `4d6kh3` - Rolls four dice and keeps the three highest (`kl` keeps the lowest ones). `4d6dl1` drops the lowest one instead
+//! - `3d6!` - Exploding dice: every die that gets its highest value is rolled again and added (at most 100 times per die)
+//! - `2d20kh+5` - Keeps the highest of two dice and adds 5
+//! 
+//! The bounds are exact, and the results of small pools are calculated while compiling so rolling them takes the same time
+//! no matter how many dice there are:
+//! 
+//! ```
+//! let gex = grand::compile_raw("4d6kh3").unwrap();
+//! assert_eq!(gex.min_number(), 3.into());
+//! assert_eq!(gex.max_number(), 18.into());
+//! 
+//! assert!(grand::compile_raw("4d6kh5").is_err()); // There are only 4 dice to keep
+//! ```
+//! 
+//! Rolls can have at most `MAX_DICE` dice, and dice at most `MAX_DICE` sides.
+//! 
+//! ### Quantifiers
+//! 
+//! Like in Regular Expressions, `{n}` repeats the expression before it. Repeated expressions generate a list of numbers
+//! with `generate_many()` (a `Float64Array` in JavaScript):
+//! 
+//! - `(1..6:int){3}` - Generates three integers between 1 and 6
+//! - `(0..1){2,5}` - Generates between 2 and 5 numbers (the amount is random too)
+//! - `(0..1){1..3}`, `(0..1){level}` - The amount can be any expression or a parameter. Amounts are always integers
+//! 
+//! Quantifiers repeat everything before them (like a precision), so they can only be used at the end of the expression
+//! or of an argument of an aggregate (see below).
+//! Bindings are generated once for the whole list. The amount of repetitions can't be negative or bigger than
+//! `MAX_REPETITIONS` (1000000), parameters used as amounts need bounds (`ParamDeclaration::bounded()`):
+//! 
+//! ```
+//! let gex = grand::compile_raw("(1..6:int){3}").unwrap();
+//! assert_eq!(gex.generate_many().len(), 3);
+//! assert!(gex.try_generate().is_err()); // Several numbers can't be generated as one
+//! 
+//! assert!(grand::compile_raw("((1..6){3})+1").is_err());
+//! assert!(grand::compile_raw("(1..6){-1}").is_err());
+//! ```
+//! 
+//! ### Aggregates
+//! 
+//! `sum()`, `min()`, `max()`, `avg()` and `kth()` combine several numbers into one. Every argument adds a number to the list,
+//! repeated arguments add one per repetition and selections without weights add all of their entries:
+//! 
+//! - `max((1..20:int){2})` - Rolls two integers and keeps the highest one ("advantage")
+//! - `sum([1..3, 5..8])` - Adds a number between 1 and 3 and a number between 5 and 8
+//! - `kth(2, (1..6:int){5})` - The second smallest of five numbers (`k` is a constant integer starting from 1)
+//! 
+//! The results have the right bounds, so they can be used in ranges and operations like any other expression. Function names
+//! must be directly followed by the parenthesis (`max(`), other words are still ignored outside of strict mode:
+//! 
+//! ```
+//! let gex = grand::compile_raw("sum((1..6:int){3})").unwrap();
+//! assert_eq!(gex.min_number(), 3.into());
+//! assert_eq!(gex.max_number(), 18.into());
+//! 
+//! let gex = grand::compile_raw("max((0..100){2})").unwrap();
+//! assert!(gex.try_generate().is_ok());
+//! 
+//! assert!(grand::compile_raw("median(1, 2)").is_err()); // Unknown function
+//! ```
+//! 
+//! Lists that can be empty (`max((0..1){0,2})`) return `GenerateError::NotEnoughValues` when no number was generated.
+//! 
+//! ### Functions
+//! 
+//! Built-in functions work on single numbers and use the same call syntax as aggregates:
+//! 
+//! - `abs(x)`, `floor(x)` and `ceil(x)`
+//! - `round(x)` and `round(x, decimals)` - Rounds half to even, like precisions (`round(0..10, 2)`)
+//! - `clamp(x, min, max)` - Keeps `x` between `min` and `max` (`clamp(-50..150, 0, 100)`)
+//! - `pow(base, exponent)` - `base` to the power of `exponent` (`pow(2, 0..10)`)
+//! - `log(x)` and `log(x, base)` - The natural logarithm of `x`, or the logarithm in the given base
+//! 
+//! The amount of arguments is checked while compiling, and so are arguments that can never be valid (`log(-5..0)`).
+//! Arguments that are only invalid sometimes (`log(0..10)` generating 0) return `GenerateError::InvalidArgument`:
+//! 
+//! ```
+//! let gex = grand::compile_raw("clamp(-50..150, 0, 100)").unwrap();
+//! assert_eq!(gex.min_number(), 0.into());
+//! assert_eq!(gex.max_number(), 100.into());
+//! 
+//! let gex = grand::compile_raw("pow(2, 0..10:int)").unwrap();
+//! assert_eq!(gex.max_number(), 1024.into());
+//! 
+//! assert!(grand::compile_raw("abs(1, 2)").is_err()); // Wrong amount of arguments
+//! assert!(grand::compile_raw("log(-5..0)").is_err()); // Invalid argument
+//! ```
+//! 
+//! ### Bindings
+//! 
+//! Bindings give a name to an expression so that its result can be used more than once. They are defined at the beginning
+//! of the program (`$name = expression;`) and generated once every time a number is generated:
+//! 
+//! - `$a = 0..10; $a..($a+5)` - Generates a number between a random number `a` and `a + 5`
+//! - `$a = 0..10:int; $b = $a*2; [$a, $b]` - Definitions can use the bindings defined before them
+//! 
+//! Using a binding that isn't defined is a compilation error, and bindings that are never used are reported as warnings.
+//! 
+//! ```
+//! let gex = grand::compile_raw("$a = 0..10; $a - $a").unwrap();
+//! assert_eq!(gex.generate(), 0.into());
+//! 
+//! assert!(grand::compile_raw("$a..10").is_err());
+//! assert_eq!(grand::compile("$a = 0..10; 5").unwrap().warnings().len(), 1);
+//! ```
+//! 
+//! ### Parameters
+//! 
+//! Parameters (`{level}`) are values supplied every time a number is generated, so the same expression can be
+//! used with different inputs. They have to be declared in the `CompileOptions`, using a parameter that isn't
+//! declared is a compilation error:
+//! 
+//! ```
+//! use grand::{CompileOptions, ParamDeclaration, Params, GenerateError};
+//! 
+//! let options = CompileOptions {
+//!     params: vec![ParamDeclaration::bounded("level", 1.into(), 100.into())],
+//!     ..Default::default()
+//! };
+//! let gex = grand::compile_raw_with("(({level}*10)..({level}*20)):int", &options).unwrap();
+//! let number = gex.generate_with_params(&Params::new().with("level", 2));
+//! assert!(number >= 20.into() && number <= 40.into());
+//! 
+//! // Missing parameters are reported when generating
+//! assert!(matches!(gex.try_generate(), Err(GenerateError::MissingParam(_))));
+//! assert!(grand::compile_raw_with("{price}", &options).is_err());
+//! ```
+//! 
+//! In JavaScript, parameters are declared with `CompileOptions.declare_param()` and supplied as an object:
+//! `grandEx.generate_with_params({ level: 2 })`.
+//! 
+//! ### Registered functions
+//! 
+//! Applications can add their own functions (currency rounding, lookup tables...) by registering them in the
+//! `CompileOptions`. They are called like built-in functions with the numbers generated by their arguments, and their
+//! declared bounds are the bounds of the call:
+//! 
+//! ```
+//! use grand::{CompileOptions, Registry};
+//! use rust_decimal::Decimal;
+//! 
+//! let mut functions = Registry::new();
+//! functions.register_bounded("double", 1, 0.into(), 200.into(), |args: &[Decimal]| args[0] * Decimal::TWO);
+//! let options = CompileOptions { functions, ..Default::default() };
+//! 
+//! let gex = grand::compile_raw_with("double(10..20)", &options).unwrap();
+//! assert_eq!(gex.min_number(), 0.into());
+//! assert!(gex.generate() >= 20.into());
+//! ```
+//! 
+//! The amount of arguments a function accepts is checked while compiling, like for built-in functions.
+//! In JavaScript, functions are registered with `CompileOptions.register_function()` or `register_bounded_function()`:
+//! `options.register_function("price", (x) => Math.round(x * 100) / 100, 1, 1)`. Exceptions and results that aren't numbers
+//! are returned as `GenerateError::InvalidResult`.
+//! 
+//! ### Precision
+//! 
+//! Ranges generate numbers with as many decimals as possible. A precision at the end of an expression
+//! sets the amount of decimals of the numbers it generates:
+//! 
+//! - `0..10:int` - Generates an integer between 0 and 10 (inclusive)
+//! - `0..1@3` - Generates a number between 0 and 1 with 3 decimals, like `0.250`
+//! - `[0..1, 2.345]@2` - Entries in selections (and sub-expressions) use the precision too. Numbers with more decimals are rounded
+//! 
+//! ```
+//! let gex = grand::compile_raw("0..1@3").unwrap();
+//! assert_eq!(gex.generate().scale(), 3);
+//! 
+//! let gex = grand::compile_raw("0,,3:int").unwrap();
+//! let number = gex.generate();
+//! assert!(number == 1.into() || number == 2.into());
+//! ```
+//! 
+//! ### Comments and strict mode
+//! 
+//! Anything after a `#` until the end of the line is a comment, and so is anything between `/*` and `*/`:
+//! 
+//! ```
+//! let gex = grand::compile_raw_strict("
+//!     ## Damage of a critical hit
+//!     20..40 /* only even numbers */ |*2
+//! ").unwrap();
+//! assert!(gex.generate() >= 20.into());
+//! ```
+//! 
+//! By default, characters that aren't part of the language are ignored. Strict mode (`compile_strict()` and
+//! `compile_raw_strict()`) reports them as errors instead, so typos like `0..1O` (letter O) don't go unnoticed:
+//! 
+//! ```
+//! assert!(grand::compile_raw("0..1O").is_ok());
+//! assert!(grand::compile_raw_strict("0..1O").is_err());
+//! ```
+//! 
 //! ## Performance
 //! 
 //! Using constant (hard-coded) numbers in constraints and ranges with small amounts of possible values makes the compiler
 //! store all possible values of the range (including constraints) in memory, making the generation very fast.  
-//! This can take a lot of memory so there's a hard limit of 1MB, this will be made configurable in later versions.
+//! This can take a lot of memory so there's a limit of 128KB per range by default. It can be changed with
+//! `CompileOptions::precalc_memory_budget` and the `compile_with()` and `compile_raw_with()` functions.
+//! 
+//! Selections choose an entry before generating it, so entries that are not chosen don't cost anything (not even
+//! rerolls) and generating numbers doesn't allocate memory. `cargo bench` measures this with nested selections.
 //! 
 //! Constraints with sub-expressions make pre-calculation impossible, since we can't know what the constraint will at runtime
 //! while we are compiling. This (and extremely large ranges that would be beyond the memory budget) makes constraints work
@@ -41,77 +312,252 @@
 //! Imagine that we want a number that is a multiple of X and not a multiple of Y.  
 //! We can try to get a random number that is a multiple of X and check if it's a multiple of Y. If it is, we generate another,
 //! if it isn't, we just return the value.  
-//! What if we end up in an endless loop? After several attempts, the program stops trying to get a number and throws an error.
-//! Unfortunately, this makes these dynamic constraints unreliable.
+//! What if we end up in an endless loop? After several attempts, the program stops trying to get a number and returns an error.
+//! Unfortunately, this makes these dynamic constraints unreliable.  
+//! The amount of attempts (1000 by default) can be changed with `CompileOptions::max_rerolls`.
+//! 
+//! `generate()` panics when this happens. Use `try_generate()` to get a `GenerateError` instead:
+//! 
+//! ```
+//! let options = grand::CompileOptions { max_rerolls: 0, ..Default::default() };
+//! let gex = grand::compile_raw_with("0..1000000|!*1", &options).unwrap();
+//! match gex.try_generate() {
+//!     Ok(number) => assert!(number < 1000000.into()),
+//!     Err(error) => println!("{}", error),
+//! }
+//! ```
+//! 
+//! Constraints that can't be written in the expression ("not a prime", "digits sum to 7") can be added from Rust with
+//! `Gex::filter()`, which rerolls the same way and returns `GenerateError::RerollsExhausted` when it gives up.
+//! `Gex::map()` transforms the numbers generated:
+//! 
+//! ```
+//! use rust_decimal::Decimal;
+//! 
+//! let gex = grand::compile_raw("10..99:int").unwrap()
+//!     .filter(|number| number % Decimal::from(11) != Decimal::ZERO) // No repeated digits
+//!     .map(|number| number / Decimal::TEN);
+//! assert!(gex.try_generate().unwrap() < Decimal::TEN);
+//! ```
+//! 
+//! The compiler doesn't print anything. Things that may not work as expected, like constraints that use rerolls,
+//! are reported as warnings through `CompileOptions::on_warning` (or `GrandEx::warnings()`):
+//! 
+//! ```
+//! use std::{cell::RefCell, rc::Rc};
+//! use grand::CompileOptions;
+//! 
+//! let warnings = Rc::new(RefCell::new(Vec::new()));
+//! let collected = warnings.clone();
+//! let options = CompileOptions {
+//!     on_warning: Some(Rc::new(move |warning| collected.borrow_mut().push(warning.message()))),
+//!     ..Default::default()
+//! };
+//! 
+//! // This range is too big to precalculate its values
+//! grand::compile_raw_with("..|*2|!*3", &options).unwrap();
+//! assert_eq!(warnings.borrow().len(), 1);
+//! ```
 //! 
 //! ## How does this look in code?
 //! 
 //! You only need to call `compile()` to create the generator, then call the `generate()` method in the generator:
 //! 
 //! ```
-//! fn main() {
-//!     let grandEx = grand::compile("0..10");
+//! fn main() -> Result<(), grand::Diagnostics> {
+//!     let grandEx = grand::compile("0..10")?;
 //!     let res = grandEx.generate();
 //!     println!("My number: {res}");
 //! 
 //!     assert!(res >= 0f64);
 //!     assert!(res <= 10f64);
+//!     Ok(())
 //! }
 //! ```
 //! 
 //! This way you can create any generator using any expression without changing any code:
 //! 
 //! ```
-//! fn main() {
-//!     let positive_number = grand::compile("0..").generate();
-//!     let negative_number = grand::compile("..0").generate();
-//!     let odd_number = grand::compile("-100..100|*1|!*2").generate();
+//! fn main() -> Result<(), grand::Diagnostics> {
+//!     let positive_number = grand::compile("0..")?.generate();
+//!     let negative_number = grand::compile("..0")?.generate();
+//!     let odd_number = grand::compile("-100..100|*1|!*2")?.generate();
+//!     Ok(())
 //! }
 //! ```
+//! 
+//! Expressions usually come from users, so compilation never panics. Malformed expressions return
+//! `Diagnostics` with every problem found in the expression, what went wrong and where:
+//! 
+//! ```
+//! assert!(grand::compile_raw("0..10|*").is_err()); // Constraint without values
+//! assert!(grand::compile_raw("(0..10").is_err()); // Unbalanced parenthesis
+//! assert!(grand::compile_raw("[]").is_err()); // Empty selection
+//! assert!(grand::compile_raw("0..10 3").is_err()); // Two expressions with nothing in between
+//! 
+//! let diagnostics = grand::compile_raw("[0..10|*0, []]").unwrap_err();
+//! assert_eq!(diagnostics.len(), 2);
+//! assert_eq!(diagnostics.to_string(), "\
+//! error: Invalid constraint value in line 0, column 9. 0 can't be used in a constraint
+//!  0 | [0..10|*0, []]
+//!    |         ^
+//! error: Empty selection in line 0, column 12. Selections need at least one entry
+//!  0 | [0..10|*0, []]
+//!    |            ^^");
+//! ```
 
 
 mod rng_traits;
+mod rng_sources;
 mod rng_functions;
 
 mod parser;
 
 use parser::parse;
 
-pub use rng_traits::Randomizable;
-pub use parser::gex::Gex;
+pub use rng_traits::{GrandRng, Randomizable};
+pub use rng_sources::{OsRng, SeededRng};
+pub use parser::gex::{Gex, MAX_REPETITIONS};
+pub use parser::gex::dice::MAX_DICE;
+pub use parser::gex::generate_error::GenerateError;
+pub use parser::gex::params::Params;
+pub use parser::gex::registry::{Arity, HostFunction, Registry};
 pub use parser::parse_error::CompilerError;
+pub use parser::parse_warning::CompilerWarning;
+pub use parser::diagnostic::{Diagnostic, DiagnosticKind, Diagnostics, Severity, Span};
+pub use parser::compile_options::{CompileOptions, ParamDeclaration, WarningHandler};
 
 use wasm_bindgen::prelude::wasm_bindgen;
-use rust_decimal::prelude::ToPrimitive;
+use js_sys::{Array, Float64Array, Object};
+use rust_decimal::{prelude::{FromPrimitive, ToPrimitive}, Decimal};
 
 /// Wrapper for Gex that returns a f64 instead of a Decimal when calling generate().  
 /// Made primarily for WASM
 #[wasm_bindgen]
 pub struct GrandEx {
-    gex: Gex
+    gex: Gex,
+    warnings: Vec<Diagnostic>,
 }
 
 #[wasm_bindgen]
 impl GrandEx {
-    /// Generates a random number and returns it as a float without
-    /// losing its precision.
+    /// Generates a random number and returns it as a float.
+    /// The number is generated with the precision of a Decimal and only
+    /// converted at the end.
+    ///
+    /// Panics if the number can't be generated or represented as a float, use `try_generate()` to get the error
     pub fn generate(&self) -> f64 {
-        self.gex.generate().to_f64().unwrap()
+        self.try_generate().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `generate()` but returns (or throws in JavaScript) an error
+    /// instead of panicking when the number can't be generated.
+    pub fn try_generate(&self) -> Result<f64, GenerateError> {
+        float(self.gex.try_generate()?)
+    }
+
+    /// Same as `generate()` but using a deterministic generator.
+    /// Generators created with the same seed generate the same numbers
+    pub fn generate_with(&self, rng: &mut SeededRng) -> f64 {
+        self.try_generate_with(rng).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `try_generate()` but using a deterministic generator
+    pub fn try_generate_with(&self, rng: &mut SeededRng) -> Result<f64, GenerateError> {
+        float(self.gex.try_generate_with(rng)?)
+    }
+
+    /// Generates a random number using the values of a JavaScript object as parameters (`{ level: 3 }`).
+    /// Throws if a parameter is missing or isn't a number
+    pub fn generate_with_params(&self, params: &Object) -> Result<f64, GenerateError> {
+        float(self.gex.try_generate_with_params(&params_from_js(params)?)?)
+    }
+
+    /// Generates a list of random numbers, one per repetition of the quantifier of the expression (`(1..6){3}`).
+    /// Expressions without a quantifier generate a single number.
+    /// Throws if a number can't be generated
+    pub fn generate_many(&self) -> Result<Float64Array, GenerateError> {
+        floats(self.gex.try_generate_many()?)
+    }
+
+    /// Same as `generate_many()` but using a deterministic generator
+    pub fn generate_many_with(&self, rng: &mut SeededRng) -> Result<Float64Array, GenerateError> {
+        floats(self.gex.try_generate_many_with(rng)?)
     }
+
+    /// Warnings found while compiling the expression
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        self.warnings.clone()
+    }
+}
+
+// Converts a generated number into a JavaScript number
+fn float(number: Decimal) -> Result<f64, GenerateError> {
+    number.to_f64().ok_or(GenerateError::NotRepresentable(number))
+}
+
+// Converts a list of generated numbers into a JavaScript array of floats
+fn floats(numbers: Vec<Decimal>) -> Result<Float64Array, GenerateError> {
+    let floats = numbers.iter()
+        .map(|number| float(*number))
+        .collect::<Result<Vec<f64>, GenerateError>>()?;
+    Ok(Float64Array::from(floats.as_slice()))
+}
+
+// Reads the values of a JavaScript object
+fn params_from_js(object: &Object) -> Result<Params, GenerateError> {
+    let mut params = Params::new();
+    for entry in Object::entries(object).iter() {
+        let entry = Array::from(&entry);
+        let name = entry.get(0).as_string().unwrap_or_default();
+        let value = entry.get(1).as_f64()
+            .and_then(Decimal::from_f64)
+            .ok_or_else(|| GenerateError::InvalidParam(name.clone()))?;
+        params.set(&name, value);
+    }
+    Ok(params)
 }
 
 /// Designed for the web, this function returns a wrapper
 /// to the underlying Gex object (GrandEx).
 /// This wrapper converts generated Decimal numbers into
-/// f64. These numbers can be printed without losing precision
+/// f64. These numbers can be printed without losing precision.
+/// Returns every problem found as `Diagnostics` if the expression is malformed
 #[wasm_bindgen]
-pub fn compile(expression: &str) -> GrandEx {
-    GrandEx { gex: parse(expression) }
+pub fn compile(expression: &str) -> Result<GrandEx, Diagnostics> {
+    compile_with(expression, &CompileOptions::default())
+}
+
+/// Same as `compile()` but unknown characters are errors instead of being ignored.
+/// Comments (`# ...` and `/* ... */`) are still allowed
+#[wasm_bindgen]
+pub fn compile_strict(expression: &str) -> Result<GrandEx, Diagnostics> {
+    compile_with(expression, &CompileOptions { strict: true, ..Default::default() })
+}
+
+/// Same as `compile()` but using the given configuration
+#[wasm_bindgen]
+pub fn compile_with(expression: &str, options: &CompileOptions) -> Result<GrandEx, Diagnostics> {
+    let (gex, warnings) = parse(expression, options)?;
+    Ok(GrandEx { gex, warnings })
 }
 
 /// Designed for other Rust applications that require random
 /// numbers with the precision of the Decimal. This function
-/// returns a Gex object without any wrapper
-pub fn compile_raw(expression: &str) -> Gex {
-    parse(expression)
+/// returns a Gex object without any wrapper.
+/// Returns every problem found as `Diagnostics` if the expression is malformed
+pub fn compile_raw(expression: &str) -> Result<Gex, Diagnostics> {
+    compile_raw_with(expression, &CompileOptions::default())
+}
+
+/// Same as `compile_raw()` but unknown characters are errors instead of being ignored.
+/// Comments (`# ...` and `/* ... */`) are still allowed
+pub fn compile_raw_strict(expression: &str) -> Result<Gex, Diagnostics> {
+    compile_raw_with(expression, &CompileOptions { strict: true, ..Default::default() })
+}
+
+/// Same as `compile_raw()` but using the given configuration.
+/// Warnings are sent to `CompileOptions::on_warning`
+pub fn compile_raw_with(expression: &str, options: &CompileOptions) -> Result<Gex, Diagnostics> {
+    parse(expression, options).map(|(gex, _)| gex)
 }
\ No newline at end of file
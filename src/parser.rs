@@ -1,6 +1,10 @@
-use gex::{constraint::Constraint, Gex};
+use compile_options::CompileOptions;
+use diagnostic::{Diagnostic, Diagnostics, Severity, Span};
+use gex::{aggregate::Aggregate, binary_operator::BinaryOperator, constraint::Constraint, dice::{Dice, Keep, MAX_DICE}, function::Function, Gex, MAX_REPETITIONS};
+use rust_decimal::prelude::ToPrimitive;
 use parse_error::CompilerError;
-use rust_decimal::{dec, prelude::FromPrimitive, Decimal};
+use parse_warning::CompilerWarning;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
 use token::Token;
 use token_type::TokenType;
 
@@ -10,396 +14,1152 @@ mod token;
 mod lexer;
 
 pub mod parse_error;
+pub mod parse_warning;
+pub mod diagnostic;
+pub mod compile_options;
 pub mod gex;
 
-const PRECALC_MEMORY_BUDGET: Decimal = dec!(131072); // 1 MB Maximum
-
 /*
- * TODO: Refactor entirely
+ * TODO: Keep refactoring
  * 
  * CURRENT PROBLEMS:
- * - Heavy Code duplication
- * - Lacking features (expressions in Selections)
+ * - Lacking features (expressions in constraints)
  * 
  * SOLUTION:
- * - More general parse_expression() that calls other functions like parse_range() or parse_selection().
- * - Heavier use of this improved parse_expression(). Useful inside parse_range() and parse_selection().
  * - Utility functions for reading arrays of numbers.
  *     - Option to allow or disallow sub-expressions (number-only mode for constraints)
- * - Add configuration
- * - Add errors and error handling. Getting a random number shouldn't (usually) panic.
  */
 
-pub fn parse(source: &str) -> Gex {
-    let tokens = lexer::tokenize(source);
-    lexer::print_tokens(&tokens);
-    parse_expression(&tokens, 0, false).0.unwrap()
+/*
+ * Returns the compiled expression and its warnings.
+ * If there are errors, every diagnostic (including warnings) is returned instead.
+ */
+pub fn parse(source: &str, options: &CompileOptions) -> Result<(Gex, Vec<Diagnostic>), Diagnostics> {
+    let (tokens, lexer_diagnostics) = lexer::tokenize(source, options.strict);
+
+    let mut parser = Parser {
+        tokens: &tokens,
+        current: 0,
+        depth: 0,
+        diagnostics: lexer_diagnostics,
+        options,
+        bindings: Vec::new(),
+        quantifiers_allowed: false,
+    };
+    let gex = parser.parse_program();
+
+    // Every token should be part of the expression
+    if let Some(token) = parser.peek() {
+        let span = token.span.to(tokens[tokens.len()-1].span);
+        parser.error(CompilerError::UnconsumedTokens(token.token_type.clone(), token.line, token.column), span);
+    }
+
+    let has_errors = parser.diagnostics.iter().any(|diagnostic| diagnostic.severity() == Severity::Error);
+    match gex {
+        Some(mut gex) if !has_errors => {
+            // Filters added to the program reroll as many times as its constraints
+            gex.set_max_rerolls(options.max_rerolls);
+            if let Some(on_warning) = &options.on_warning {
+                parser.diagnostics.iter().for_each(|warning| on_warning(warning));
+            }
+            Ok((gex, parser.diagnostics))
+        },
+        _ => Err(Diagnostics::new(source, parser.diagnostics)),
+    }
 }
 
 /*
- * The entire code is an expression (or multiple)
- * This expression can contain sub-expressions.
- * This function returns a Gex with all the sub-expressions
- * already included by using recursion.
+ * Recursive descent parser.
  * 
- * INFO: allow_comma indicates that this function has been called to read part of a list
- *       and that we should stop reading as soon as we find a comma.
+ * Errors don't stop the parser. They are stored as diagnostics and the parser skips
+ * the problematic tokens so that every error in the program can be reported at once.
+ * Parsing functions return None when they found an error.
  */
-fn parse_expression(tokens: &[Token], mut index: usize, allow_comma: bool) -> (Result<Gex, CompilerError>, usize) {
-    // At the beginning of an expression we expect:
-    // - A number (Gex with Expression of type Number)
-    // - A Range operator followed by a number or sub-expression
-    // - A sub-expression. This is indicated by a parenthesis.
-    //    We need to increment a counter when we see a LParen, decrement it with every RParen
-    //    and call this function (parse_expression()) with the slice between parenthesis as parameter
-    // - A Selection. This is indicated by a bracket. It's parsed in a similar way as sub-expressions,
-    //    but this time we call parse_selection().
-
-    let mut accumulator: Option<Gex> = None;
-
-    while index < tokens.len() {
-        match tokens[index].token_type {
-            token_type::TokenType::Number => {
-                let x_num = Decimal::from_str_exact(&tokens[index].content).expect("lexing/parsing error. NaN found in numerical token");
-                let x = Gex::from_num(x_num);
-                
-                index += 1;
-                accumulator = Some(x);
-            },
-            token_type::TokenType::RangeCC |
-            token_type::TokenType::RangeOO |
-            token_type::TokenType::RangeCO |
-            token_type::TokenType::RangeOC => {
-                // Create range with X being the value in the accumulator
-                let x = match accumulator {
-                    Some(gex) => gex,
-                    None => Gex::from_num(i64::MIN.into()),
-                };
-                let res = parse_range(x, tokens, index);
-                index = res.1;
-                match res.0 {
-                    Ok(gex) => accumulator = Some(gex),
-                    Err(error) => return (Err(error), index), // TODO: Accumulate errors, show every error at one after compilation attempt
+struct Parser<'a> {
+    tokens: &'a [Token],
+    current: usize,
+    depth: usize, // Amount of parenthesis and brackets we are inside of
+    diagnostics: Vec<Diagnostic>,
+    options: &'a CompileOptions,
+    bindings: Vec<Binding<'a>>, // Bindings defined so far, in order
+    quantifiers_allowed: bool, // Whether the next expression can be repeated (`(1..6){3}`)
+}
+
+/*
+ * A binding defined in the program (`$a = 0..10;`).
+ * The bounds are the ones of its definition.
+ */
+struct Binding<'a> {
+    token: &'a Token,
+    min_number: Decimal,
+    max_number: Decimal,
+    used: bool,
+}
+
+impl<'a> Parser<'a> {
+    /*
+     * A program is a list of binding definitions (`$a = 0..10;`) followed by an expression.
+     * Bindings can be used in the expression and in the definitions that come after them.
+     */
+    fn parse_program(&mut self) -> Option<Gex> {
+        let mut definitions: Vec<Gex> = Vec::new();
+        let mut failed = false;
+
+        while let (Some(name), Some(equals)) = (self.peek(), self.tokens.get(self.current + 1)) {
+            if name.token_type != TokenType::Binding || equals.token_type != TokenType::Equals {
+                break;
+            }
+            self.advance();
+            self.advance();
+
+            let definition = self.parse_expression(false);
+            match self.peek() {
+                Some(token) if token.token_type == TokenType::Semicolon => { self.advance(); },
+                Some(token) => {
+                    self.error(CompilerError::UnexpectedToken(vec![TokenType::Semicolon], token.token_type.clone(), token.line, token.column), token.span);
+                    failed = true;
+                },
+                // There's no expression after the definitions
+                None => {
+                    self.error(CompilerError::NoExpressions, self.current_span());
+                    failed = true;
                 }
             }
-            token_type::TokenType::LBrack => {
-                let subex_end_index = find_selection_end(tokens, index);
-                let res = parse_selection(&tokens[index+1..subex_end_index]);
-                index = subex_end_index+1;
-                match res {
-                    Ok(gex) => accumulator = Some(gex),
-                    Err(error) => return (Err(error), index), // TODO: Accumulate errors, show every error at one after compilation attempt
+
+            if self.bindings.iter().any(|binding| binding.token.content == name.content) {
+                self.error(CompilerError::DuplicateBinding(name.content.clone(), name.line, name.column), name.span);
+                failed = true;
+                continue;
+            }
+            // Failed definitions are still added so that using them isn't reported as an unknown binding
+            let (min_number, max_number) = definition.as_ref().map_or((Decimal::ZERO, Decimal::ZERO), |gex| (gex.min_number(), gex.max_number()));
+            self.bindings.push(Binding { token: name, min_number, max_number, used: false });
+            match definition {
+                Some(gex) => definitions.push(gex),
+                None => failed = true,
+            }
+        }
+
+        // The expression can generate a list of numbers (`(1..6){3}`)
+        self.quantifiers_allowed = true;
+        let body = if failed && self.peek().is_none() {
+            None
+        } else {
+            self.parse_expression(false)
+        };
+
+        let unused: Vec<&Token> = self.bindings.iter().filter(|binding| !binding.used).map(|binding| binding.token).collect();
+        for name in unused {
+            self.warning(CompilerWarning::UnusedBinding(name.content.clone(), name.line, name.column), name.span);
+        }
+
+        if failed {
+            return None
+        }
+        let body = body?;
+        if definitions.is_empty() {
+            Some(body)
+        } else {
+            Some(Gex::from_bindings(definitions, body))
+        }
+    }
+
+    /*
+     * The entire code is an expression (or multiple)
+     * This expression can contain sub-expressions.
+     * This function returns a Gex with all the sub-expressions
+     * already included by using recursion.
+     * 
+     * INFO: in_list indicates that this function has been called to read part of a list
+     *       and that we should stop reading as soon as we find a comma.
+     */
+    fn parse_expression(&mut self, in_list: bool) -> Option<Gex> {
+        // At the beginning of an expression we expect:
+        // - A number (Gex with Expression of type Number)
+        // - A Range operator followed by a number or sub-expression
+        // - A sub-expression. This is indicated by a parenthesis.
+        // - A Selection. This is indicated by a bracket.
+        // These can be combined with arithmetic operators (+ - * / %) and followed by a precision.
+        // The expression ends at the end of the program, a closing delimiter or a comma (in lists)
+
+        let start = self.current;
+        let mut accumulator: Option<Gex> = None;
+        let mut failed = false;
+        // Only the caller of this expression can allow quantifiers, sub-expressions can't use them unless their caller allows them too
+        let quantifiers_allowed = std::mem::take(&mut self.quantifiers_allowed);
+
+        while let Some(token) = self.peek() {
+            match token.token_type {
+                // Precision applies to everything before it
+                TokenType::Precision => {
+                    self.advance();
+                    match (accumulator.as_mut(), parse_precision(token)) {
+                        (Some(gex), Ok(scale)) => {
+                            gex.set_precision(scale);
+                            // Constant ranges without numbers at this precision (`0.2..0.8:int`)
+                            if gex.min_number() > gex.max_number() {
+                                let first_token = &self.tokens[start];
+                                self.error(CompilerError::EmptySupport(first_token.line, first_token.column), first_token.span.to(token.span));
+                                failed = true;
+                            }
+                        },
+                        (_, Err(error)) => {
+                            self.error(error, token.span);
+                            failed = true;
+                        },
+                        (None, Ok(_)) => {
+                            // Precision of an expression that failed, it was already reported
+                            if !failed {
+                                self.error(CompilerError::UnexpectedToken(
+                                    vec![TokenType::Number, TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO, TokenType::LBrack, TokenType::LParen],
+                                    token.token_type.clone(),
+                                    token.line,
+                                    token.column
+                                ), token.span);
+                            }
+                            failed = true;
+                        }
+                    }
+                },
+                // Quantifiers repeat everything before them (`(1..6){3}`). A parameter after an expression is a quantifier too (`(1..6){level}`)
+                TokenType::LBrace |
+                TokenType::Param if accumulator.is_some() || token.token_type == TokenType::LBrace => {
+                    let counts = if token.token_type == TokenType::LBrace {
+                        self.parse_quantifier()
+                    } else {
+                        self.parse_primary().map(|count| (count, None))
+                    };
+                    match (accumulator.take(), counts) {
+                        (Some(gex), Some((min_count, max_count))) => {
+                            accumulator = self.repeat(token, gex, min_count, max_count, quantifiers_allowed);
+                            failed |= accumulator.is_none();
+                        },
+                        (None, _) if !failed => {
+                            self.error(CompilerError::UnexpectedToken(
+                                vec![TokenType::Number, TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO, TokenType::LBrack, TokenType::LParen],
+                                token.token_type.clone(),
+                                token.line,
+                                token.column
+                            ), token.span);
+                            failed = true;
+                        },
+                        // Errors in the expression or in the counts were already reported
+                        _ => failed = true,
+                    }
+                },
+                TokenType::RParen |
+                TokenType::RBrack |
+                TokenType::RBrace => {
+                    // Closing delimiters end sub-expressions, selections and quantifiers, they are consumed by the caller.
+                    // If we aren't inside of one, it was never opened
+                    if self.depth > 0 {
+                        break;
+                    }
+                    self.advance();
+                    self.error(CompilerError::UnbalancedDelimiter(token.token_type.clone(), token.line, token.column), token.span);
+                    failed = true;
+                },
+                // INFO: This ends the expression and is only valid when reading in list mode
+                TokenType::Comma if in_list => break,
+                // The weight of an entry, read by parse_selection
+                TokenType::Weight if in_list => break,
+                // Selections read their modifiers, anything else can't have them
+                TokenType::Modifier => {
+                    self.advance();
+                    self.error(CompilerError::InvalidModifier(token.content.clone(), token.line, token.column), token.span);
+                    failed = true;
+                },
+                // Ends the definition of a binding, the caller consumes it
+                TokenType::Semicolon if self.depth == 0 => break,
+                _ if starts_operand(&token.token_type) => {
+                    // A number, selection or sub-expression can't follow a complete expression, like in `5 7`.
+                    // It would silently replace the previous value
+                    let juxtaposed = accumulator.is_some();
+                    match self.parse_sum() {
+                        Some(gex) => accumulator = Some(gex),
+                        None => failed = true,
+                    }
+                    if juxtaposed {
+                        self.error(CompilerError::JuxtaposedExpression(token.line, token.column), token.span.to(self.previous_span()));
+                        failed = true;
+                    }
+                },
+                _ => {
+                    // Unexpected Token error
+                    self.advance();
+                    self.error(CompilerError::UnexpectedToken(
+                        vec![TokenType::Number, TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO, TokenType::LBrack, TokenType::LParen],
+                        token.token_type.clone(),
+                        token.line,
+                        token.column
+                    ), token.span);
+                    failed = true;
                 }
+            } // match token type
+        } // while loop
+
+        if failed {
+            return None
+        }
+        // Running out of tokens inside of a sub-expression or selection is already reported as an unbalanced delimiter
+        let unclosed = self.depth > 0 && self.peek().is_none();
+        if accumulator.is_none() && !unclosed {
+            self.error(CompilerError::NoExpressions, self.current_span());
+        }
+        accumulator
+    }
+
+    /*
+     * Arithmetic. From lowest to highest precedence:
+     * - Sums and subtractions (parse_sum)
+     * - Multiplications, divisions and remainders (parse_product)
+     * - Ranges (parse_term), so `0..10*2` is `(0..10)*2`
+     * - Unary minus and plus (parse_unary)
+     * - Numbers, selections and sub-expressions (parse_primary)
+     * 
+     * Both operands are always parsed so that every error is reported
+     */
+    fn parse_sum(&mut self) -> Option<Gex> {
+        let mut left = self.parse_product();
+        while let Some(operator_token) = self.peek().filter(|token| matches!(token.token_type, TokenType::Plus | TokenType::Minus)) {
+            self.advance();
+            let operator = if operator_token.token_type == TokenType::Plus { BinaryOperator::Add } else { BinaryOperator::Subtract };
+            let right = self.parse_product();
+            left = self.binary(operator, operator_token, left, right);
+        }
+        left
+    }
+    fn parse_product(&mut self) -> Option<Gex> {
+        let mut left = self.parse_term();
+        while let Some(operator_token) = self.peek() {
+            let operator = match operator_token.token_type {
+                TokenType::CMultOf => BinaryOperator::Multiply,
+                TokenType::Slash => BinaryOperator::Divide,
+                TokenType::Percent => BinaryOperator::Remainder,
+                _ => break,
+            };
+            self.advance();
+            let right = self.parse_term();
+            left = self.binary(operator, operator_token, left, right);
+        }
+        left
+    }
+    /*
+     * A range or one of its bounds. Ranges can be chained, the range on the left is the
+     * lower bound of the next one. The lower bound can be omitted (`..10`)
+     */
+    fn parse_term(&mut self) -> Option<Gex> {
+        let first_token = self.peek();
+        let mut term = if self.peek().is_some_and(|token| is_range(&token.token_type)) {
+            Some(Gex::from_num(self.options.range_min))
+        } else {
+            self.parse_unary()
+        };
+        while self.peek().is_some_and(|token| is_range(&token.token_type)) {
+            // Create range with X being the term. If X failed, the range is still parsed to report its errors
+            let failed = term.is_none();
+            let x = term.unwrap_or_else(|| Gex::from_num(self.options.range_min));
+            term = self.parse_range(x, first_token).filter(|_| !failed);
+        }
+        term
+    }
+    fn parse_unary(&mut self) -> Option<Gex> {
+        match self.peek() {
+            Some(token) if token.token_type == TokenType::Minus => {
+                self.advance();
+                // -X is 0 - X. Negative numbers are calculated while compiling
+                let operand = self.parse_unary()?;
+                Some(Gex::from_binary(BinaryOperator::Subtract, Gex::from_num(Decimal::ZERO), operand))
             },
-            token_type::TokenType::LParen => {
-                let subex_end_index = find_subexpression_end(tokens, index);
-                let (res, _) = parse_expression(&tokens[index+1..subex_end_index], 0, false);
-    
-                // If there was an error we propagate it
-                // Otherwise we put the result into the accumulator
-                index = subex_end_index+1;
-                match res {
-                    Ok(gex) => accumulator = Some(gex),
-                    Err(error) => return (Err(error), index), // TODO: Accumulate errors, show every error at one after compilation attempt
+            Some(token) if token.token_type == TokenType::Plus => {
+                self.advance();
+                self.parse_unary()
+            },
+            _ => self.parse_primary(),
+        }
+    }
+    fn parse_primary(&mut self) -> Option<Gex> {
+        match self.peek() {
+            Some(token) if token.token_type == TokenType::Number => {
+                self.advance();
+                match parse_number(token) {
+                    Ok(num) => Some(Gex::from_num(num)),
+                    Err(error) => {
+                        self.error(error, token.span);
+                        None
+                    }
                 }
             },
-            token_type::TokenType::Comma => {
-                // INFO: This ends the expression and is only valid when reading in list mode
-                if allow_comma {
-                    index += 1;
-                    break; // Finish parsing early
-                } else {
-                    return (Err(CompilerError::UnexpectedToken(
-                        vec![TokenType::Number, TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO, TokenType::LBrack, TokenType::LParen],
-                        tokens[index].token_type.clone(),
-                        tokens[index].line,
-                        tokens[index].column
-                    )), index)
+            Some(token) if token.token_type == TokenType::LBrack => self.parse_selection(),
+            Some(token) if token.token_type == TokenType::LParen => self.parse_subexpression(),
+            Some(token) if token.token_type == TokenType::Identifier => self.parse_call(),
+            Some(token) if token.token_type == TokenType::Dice => {
+                self.advance();
+                match parse_dice(token) {
+                    Ok(dice) => Some(Gex::from_dice(dice)),
+                    Err(error) => {
+                        self.error(error, token.span);
+                        None
+                    }
                 }
-            }
+            },
+            Some(token) if token.token_type == TokenType::Param => {
+                self.advance();
+                match self.options.params.iter().find(|param| param.name == token.content) {
+                    Some(param) => Some(Gex::from_param(&param.name, param.min, param.max)),
+                    None => {
+                        self.error(CompilerError::UnknownParam(token.content.clone(), token.line, token.column), token.span);
+                        None
+                    }
+                }
+            },
+            Some(token) if token.token_type == TokenType::Binding => {
+                self.advance();
+                match self.bindings.iter().position(|binding| binding.token.content == token.content) {
+                    Some(index) => {
+                        let binding = &mut self.bindings[index];
+                        binding.used = true;
+                        Some(Gex::from_binding_ref(index, binding.min_number, binding.max_number))
+                    },
+                    None => {
+                        self.error(CompilerError::UnknownBinding(token.content.clone(), token.line, token.column), token.span);
+                        None
+                    }
+                }
+            },
             _ => {
-                // Throw Unexpected Token error
-                return (Err(CompilerError::UnexpectedToken(
-                    vec![TokenType::Number, TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO, TokenType::LBrack, TokenType::LParen],
-                    tokens[index].token_type.clone(),
-                    tokens[index].line,
-                    tokens[index].column
-                )), index)
+                // Only operators can get us here, the next token is left for the caller (it could close a sub-expression)
+                let operator = &self.tokens[self.current.saturating_sub(1)];
+                self.error(CompilerError::MissingOperand(operator.token_type.clone(), operator.line, operator.column), operator.span.to(self.current_span()));
+                None
             }
-        } // match token type
-    } // while loop
-    
-    // TODO: Check if all tokens were actually consumed
-    match accumulator {
-        Some(gex) => (Ok(gex), index),
-        None => (Err(CompilerError::NoExpressions), index),
+        }
     }
-}
+    /*
+     * Creates the operation if both operands are valid.
+     * Divisions by a constant 0 are reported here, the rest of them are only known at runtime.
+     */
+    fn binary(&mut self, operator: BinaryOperator, operator_token: &Token, left: Option<Gex>, right: Option<Gex>) -> Option<Gex> {
+        let (left, right) = (left?, right?);
+        let divides = matches!(operator, BinaryOperator::Divide | BinaryOperator::Remainder);
+        if divides && right.min_number().is_zero() && right.max_number().is_zero() {
+            self.error(CompilerError::DivisionByZero(operator_token.line, operator_token.column), operator_token.span.to(self.previous_span()));
+            return None
+        }
+        Some(Gex::from_binary(operator, left, right))
+    }
+
+    /*
+     * Function calls: `name(argument, argument, ...)`.
+     * Aggregates combine the numbers generated by their arguments, which can be repeated (`sum((1..6){3})`).
+     * `kth(k, ...)` gets the k-th smallest number, k has to be a constant integer greater than 0.
+     */
+    fn parse_call(&mut self) -> Option<Gex> {
+        let name = self.advance();
+        let l_paren = self.advance(); // Identifiers are always followed by a parenthesis
+        // Registered functions replace built-in ones
+        let options = self.options;
+        let host = options.functions.get(&name.content);
+        let aggregate = Aggregate::from_name(&name.content).filter(|_| host.is_none());
+        let function = Function::from_name(&name.content).filter(|_| host.is_none());
+        let mut args: Vec<(&Token, Gex)> = Vec::new();
+        let mut failed = false;
 
-// TODO: Refactor into READ_VECTOR
-fn parse_selection(tokens: &[Token]) -> Result<Gex, CompilerError> {
-    let mut index = 0;
-    // Find numbers
-    let mut entries: Vec<Gex> = Vec::new();
+        self.depth += 1;
+        while let Some(first_token) = self.peek().filter(|token| token.token_type != TokenType::RParen) {
+            self.quantifiers_allowed = aggregate.is_some();
+            match self.parse_expression(true) {
+                Some(gex) => args.push((first_token, gex)),
+                None => failed = true,
+            }
+            // Arguments are separated by commas
+            if !self.check(TokenType::Comma) {
+                break;
+            }
+            self.advance();
+        }
+        self.depth -= 1;
 
-    loop {
-        if index >= tokens.len() {
-            break;
+        let closed = self.expect_closing(l_paren, TokenType::RParen);
+        if host.is_none() && aggregate.is_none() && function.is_none() {
+            self.error(CompilerError::UnknownFunction(name.content.clone(), name.line, name.column), name.span);
+            return None
         }
+        if failed || !closed {
+            return None
+        }
+        if let Some(host) = host {
+            if !host.accepts(args.len()) {
+                self.error(CompilerError::WrongArgumentCount(name.content.clone(), args.len(), name.line, name.column), name.span.to(self.previous_span()));
+                return None
+            }
+            return Some(Gex::from_host(host.clone(), args.into_iter().map(|(_, gex)| gex).collect()))
+        }
+        let Some(mut aggregate) = aggregate else {
+            return self.call_function(name, function?, args)
+        };
 
-        let (res, new_index) = parse_expression(&tokens, index, true);
-        index = new_index;
-        entries.push(res?);
+        let min_args = if matches!(aggregate, Aggregate::Kth(_)) { 2 } else { 1 };
+        if args.len() < min_args {
+            self.error(CompilerError::WrongArgumentCount(name.content.clone(), args.len(), name.line, name.column), name.span.to(self.previous_span()));
+            return None
+        }
+        if let Aggregate::Kth(_) = aggregate {
+            let (k_token, k) = args.remove(0);
+            let constant = (k.min_number() == k.max_number()).then_some(k.min_number());
+            match constant.filter(|k| k.fract().is_zero()).and_then(|k| k.to_usize()).filter(|k| *k > 0) {
+                Some(k) => aggregate = Aggregate::Kth(k),
+                None => {
+                    self.error(CompilerError::InvalidArgument(name.content.clone(), k_token.line, k_token.column), k_token.span);
+                    return None
+                }
+            }
+        }
+        Some(Gex::from_aggregate(aggregate, args.into_iter().map(|(_, gex)| gex).collect()))
     }
+    /*
+     * Creates the call to a built-in function (`round(0..10, 2)`) once its arguments are parsed.
+     * The amount of arguments must be one the function accepts, and arguments that can never be valid are reported.
+     * The amount of decimals of round() is generated as an integer unless it has a precision, like the counts of quantifiers.
+     */
+    fn call_function(&mut self, name: &Token, function: Function, mut args: Vec<(&Token, Gex)>) -> Option<Gex> {
+        if !function.accepts(args.len()) {
+            self.error(CompilerError::WrongArgumentCount(name.content.clone(), args.len(), name.line, name.column), name.span.to(self.previous_span()));
+            return None
+        }
+        let bounds: Vec<(Decimal, Decimal)> = args.iter().map(|(_, gex)| (gex.min_number(), gex.max_number())).collect();
+        if let Some(index) = function.invalid_argument(&bounds) {
+            let arg_token = args[index].0;
+            self.error(CompilerError::InvalidArgument(name.content.clone(), arg_token.line, arg_token.column), arg_token.span);
+            return None
+        }
+        if let (Function::Round, Some((_, decimals))) = (function, args.get_mut(1)) {
+            if decimals.precision().is_none() {
+                decimals.set_precision(0);
+            }
+        }
+        Some(Gex::from_function(function, args.into_iter().map(|(_, gex)| gex).collect()))
+    }
+    /*
+     * Reads the amount of repetitions of a quantifier: `{3}` or `{min,max}`.
+     * Counts can be any expression
+     */
+    fn parse_quantifier(&mut self) -> Option<(Gex, Option<Gex>)> {
+        let l_brace = self.advance();
 
-    Ok(Gex::from_select(entries))
-}
+        self.depth += 1;
+        let min_count = self.parse_expression(true);
+        let max_count = if self.check(TokenType::Comma) {
+            self.advance();
+            Some(self.parse_expression(true))
+        } else {
+            None
+        };
+        self.depth -= 1;
 
-/*
- * Takes the first Gex (x) and the tokens and index for the next 
- */
-fn parse_range(x: Gex, tokens: &[Token], mut index: usize) -> (Result<Gex, CompilerError>, usize) {
-    let (x_open, y_open) = match tokens[index].token_type {
-        TokenType::RangeCC => (false, false),
-        TokenType::RangeOO => (true, true),
-        TokenType::RangeCO => (false, true),
-        TokenType::RangeOC => (true, false),
-        _ => {
-            return (Err(CompilerError::UnexpectedToken(
-                vec![TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO],
-                tokens[index].token_type.clone(),
-                tokens[index].line,
-                tokens[index].column
-            )), index)
+        let closed = self.expect_closing(l_brace, TokenType::RBrace);
+        match (min_count, max_count) {
+            (Some(min_count), None) if closed => Some((min_count, None)),
+            (Some(min_count), Some(Some(max_count))) if closed => Some((min_count, Some(max_count))),
+            _ => None,
+        }
+    }
+    /*
+     * Creates the repetition of an expression.
+     * Counts are whole numbers, they are generated as integers unless they have a precision. Their bounds must be between
+     * 0 and MAX_REPETITIONS (and the minimum can't be bigger than the maximum).
+     * Quantifiers can only be used where lists of numbers are allowed, and lists can't be repeated.
+     */
+    fn repeat(&mut self, quantifier: &Token, gex: Gex, mut min_count: Gex, mut max_count: Option<Gex>, allowed: bool) -> Option<Gex> {
+        let span = quantifier.span.to(self.previous_span());
+        if !allowed || gex.is_repeated() {
+            self.error(CompilerError::MisplacedQuantifier(quantifier.line, quantifier.column), span);
+            return None
         }
-    };
 
-    // Next should be a number, sub-expression or selection
-    index += 1;
-    let y: Gex = if tokens.len() > index {
-        // If we still have tokens we continue to read. We expect a number (or subexpression / selection)
-        let token_y = &tokens[index];
-        match &token_y.token_type {
-            TokenType::Number => {
-                let y_num = Decimal::from_str_exact(&token_y.content).expect("lexing/parsing error. NaN found in numerical token");
-                index += 1;
-                Gex::from_num(y_num)
+        let max_repetitions = Decimal::from(MAX_REPETITIONS);
+        let (lowest, highest) = (min_count.min_number(), max_count.as_ref().unwrap_or(&min_count).max_number());
+        let inverted = max_count.as_ref().is_some_and(|max_count| max_count.max_number() < min_count.min_number());
+        if lowest < Decimal::ZERO || highest > max_repetitions || inverted {
+            self.error(CompilerError::InvalidQuantifier(quantifier.line, quantifier.column), span);
+            return None
+        }
+
+        for count in std::iter::once(&mut min_count).chain(max_count.as_mut()) {
+            if count.precision().is_none() {
+                count.set_precision(0);
             }
-            TokenType::LBrack => {
-                let subex_end_index = find_selection_end(tokens, index);
-                let res = parse_selection(&tokens[index+1..subex_end_index]);
-                index = subex_end_index+1;
-                match res {
-                    Ok(gex) => gex,
-                    Err(err) => return (Err(err), index),
+        }
+        Some(Gex::from_repeat(gex, min_count, max_count))
+    }
+
+    fn parse_subexpression(&mut self) -> Option<Gex> {
+        let l_paren = self.advance();
+
+        self.depth += 1;
+        let gex = self.parse_expression(false);
+        self.depth -= 1;
+
+        let closed = self.expect_closing(l_paren, TokenType::RParen);
+        gex.filter(|_| closed)
+    }
+
+    // TODO: Refactor into READ_VECTOR
+    fn parse_selection(&mut self) -> Option<Gex> {
+        let l_brack = self.advance();
+        let mut entries: Vec<Gex> = Vec::new();
+        let mut weights: Vec<Decimal> = Vec::new();
+        let mut last_weight: Option<&Token> = None; // The selection is only weighted if an entry has a weight
+        let mut failed = false;
+
+        self.depth += 1;
+        if let Some(r_brack) = self.peek().filter(|token| token.token_type == TokenType::RBrack) {
+            self.error(CompilerError::EmptySelection(l_brack.line, l_brack.column), l_brack.span.to(r_brack.span));
+            failed = true;
+        } else {
+            loop {
+                match self.parse_expression(true) {
+                    Some(gex) => entries.push(gex),
+                    None => failed = true,
+                }
+
+                // An entry can have a weight (`:70`), entries without one weigh 1
+                let mut weight = Decimal::ONE;
+                if let Some(weight_token) = self.peek().filter(|token| token.token_type == TokenType::Weight) {
+                    self.advance();
+                    match Decimal::from_str_exact(&weight_token.content).ok().filter(|value| *value >= Decimal::ZERO) {
+                        Some(value) => weight = value,
+                        None => {
+                            self.error(CompilerError::InvalidWeight(weight_token.content.clone(), weight_token.line, weight_token.column), weight_token.span);
+                            failed = true;
+                        }
+                    }
+                    last_weight = Some(weight_token);
                 }
+                weights.push(weight);
+
+                // Entries are separated by commas. A trailing comma is allowed
+                if self.check(TokenType::Comma) {
+                    self.advance();
+                    if !self.check(TokenType::RBrack) {
+                        continue;
+                    }
+                }
+                break;
+            }
+        }
+        self.depth -= 1;
+
+        let closed = self.expect_closing(l_brack, TokenType::RBrack);
+
+        // `|uniform` picks the entries depending on the amount of values they can generate. Weights would be overwritten
+        let uniform = self.peek().filter(|token| token.token_type == TokenType::Modifier);
+        if let Some(modifier) = uniform {
+            self.advance();
+            if modifier.content != "uniform" || last_weight.is_some() {
+                self.error(CompilerError::InvalidModifier(modifier.content.clone(), modifier.line, modifier.column), modifier.span);
+                failed = true;
+            }
+        }
+
+        if failed || !closed {
+            return None
+        }
+        if uniform.is_some() {
+            return Some(Gex::from_uniform_select(entries))
+        }
+        let Some(weight_token) = last_weight else {
+            return Some(Gex::from_select(entries))
+        };
+        if weights.iter().all(|weight| weight.is_zero()) {
+            self.error(CompilerError::ZeroWeights(l_brack.line, l_brack.column), l_brack.span.to(self.previous_span()));
+            return None
+        }
+        let gex = Gex::from_weighted_select(entries, &weights);
+        if gex.is_none() {
+            // The weights can't be converted into integers
+            self.error(CompilerError::InvalidWeight(weight_token.content.clone(), weight_token.line, weight_token.column), weight_token.span);
+        }
+        gex
+    }
+
+    /*
+     * Takes the first Gex (x), reads the range operator and the second Gex (y)
+     */
+    fn parse_range(&mut self, x: Gex, first_token: Option<&'a Token>) -> Option<Gex> {
+        let range_token = self.advance();
+        let first_token = first_token.unwrap_or(range_token); // The range starts with its operator if X is omitted
+        let (x_open, y_open) = match range_token.token_type {
+            TokenType::RangeCC => (false, false),
+            TokenType::RangeOO => (true, true),
+            TokenType::RangeCO => (false, true),
+            TokenType::RangeOC => (true, false),
+            _ => {
+                self.error(CompilerError::UnexpectedToken(
+                    vec![TokenType::RangeCC, TokenType::RangeCO, TokenType::RangeOC, TokenType::RangeOO],
+                    range_token.token_type.clone(),
+                    range_token.line,
+                    range_token.column
+                ), range_token.span);
+                return None
             }
-            TokenType::LParen => {
-                let subex_end_index = find_subexpression_end(tokens, index);
-                let (res, _) = parse_expression(&tokens[index+1..subex_end_index], 0, false);
-                index = subex_end_index+1;
-                match res {
-                    Ok(gex) => gex,
-                    Err(err) => return (Err(err), index),
+        };
+
+        // Next should be a number, sub-expression or selection (it can be negative)
+        let y: Option<Gex> = match self.peek() {
+            // Ok so we have no more tokens to read, let's use the default value
+            None => Some(Gex::from_num(self.options.range_max)),
+            Some(token_y) => match token_y.token_type {
+                TokenType::Number |
+                TokenType::Dice |
+                TokenType::Identifier |
+                TokenType::LBrack |
+                TokenType::LParen |
+                TokenType::Binding |
+                TokenType::Param |
+                TokenType::Minus |
+                TokenType::Plus => self.parse_unary(),
+                // The upper bound can be omitted as long as the range ends here (it's followed by constraints, a precision
+                // or an operator, it's an entry in a list or it's the end of a sub-expression or a binding definition)
+                TokenType::Constraint |
+                TokenType::Semicolon |
+                TokenType::Precision |
+                TokenType::CMultOf |
+                TokenType::Slash |
+                TokenType::Percent |
+                TokenType::Comma |
+                TokenType::Weight |
+                TokenType::LBrace |
+                TokenType::RParen |
+                TokenType::RBrack |
+                TokenType::RBrace => Some(Gex::from_num(self.options.range_max)),
+                _ => {
+                    self.advance();
+                    self.error(CompilerError::UnterminatedRange(
+                        token_y.token_type.clone(),
+                        range_token.line,
+                        range_token.column
+                    ), range_token.span.to(token_y.span));
+                    None
                 }
             }
-            _ => Gex::from_num(i64::MAX.into())
+        };
+
+        // Constraints are parsed even if Y failed so that their errors are reported too
+        let first_pipe = self.peek().filter(|token| token.token_type == TokenType::Constraint);
+        let (c_mult_of, c_not_mult_of) = self.parse_constraints()?;
+        let y = y?;
+        if is_certainly_empty(&x, &y, x_open, y_open, c_mult_of) {
+            self.error(CompilerError::EmptySupport(first_token.line, first_token.column), first_token.span.to(self.previous_span()));
+            return None
         }
-    } else {
-        // Ok so we have no more tokens to read, let's use the default value
-        Gex::from_num(i64::MAX.into())
-    };
+        let range = Gex::from_range(x, y, x_open, y_open);
 
-    if tokens.len() > index {
-        if let TokenType::Constraint = tokens[index].token_type {
-            parse_constraints(Gex::from_range(x, y, x_open, y_open), tokens, index)
-        } else {
-            (Ok(Gex::from_range(x, y, x_open, y_open)), index)
+        match first_pipe {
+            Some(pipe) => self.apply_constraints(range, c_mult_of, c_not_mult_of, pipe),
+            None => Some(range),
         }
-    } else {
-        (Ok(Gex::from_range(x, y, x_open, y_open)), index)
     }
-}
 
-/*
- * TODO: NEEDS IMMEDIATE REFACTORING
- */
-fn parse_constraints(mut gex: Gex, tokens: &[Token], mut index: usize) -> (Result<Gex, CompilerError>, usize) {
-    let mut c_mult_of: Option<Decimal> = None;
-    let mut c_not_mult_of: Option<Vec<Decimal>> = None;
-
-    while index < tokens.len() {
-        let token = &tokens[index];
-
-        // Exit if this is not a constraint
-        if token.token_type != TokenType::Constraint {
-            return (
-                Err(CompilerError::UnexpectedToken(
-                    vec![TokenType::Constraint],
-                    token.token_type.clone(),
-                    token.line,
-                    token.column
-                )),
-                index
-            )
-        }
-        index += 1;
-
-        // Parse constraint
-        // We expect a NOT or a Constraint
-        // If we get a NOT, we expect a Constraint afterwards
-        let mut constraint = match tokens[index].token_type {
+    /*
+     * Reads every constraint after a range.
+     * Returns the Least Common Multiple for MultipleOf constraints and the blacklisted
+     * numbers for NotMultipleOf constraints.
+     */
+    fn parse_constraints(&mut self) -> Option<(Option<Decimal>, Option<Vec<Decimal>>)> {
+        let mut c_mult_of: Option<Decimal> = None;
+        let mut c_not_mult_of: Option<Vec<Decimal>> = None;
+        let mut failed = false;
+
+        while let Some(pipe) = self.peek().filter(|token| token.token_type == TokenType::Constraint) {
+            self.advance();
+            match self.parse_constraint(pipe) {
+                Some(Constraint::MultipleOf(lcm)) => c_mult_of = Some(lcm),
+                Some(Constraint::NotMultipleOf(items)) => c_not_mult_of = Some(items),
+                None => {
+                    // Skip the rest of this constraint, the next one (or the next entry in a list) could still be checked
+                    failed = true;
+                    while let Some(token) = self.peek() {
+                        if matches!(token.token_type, TokenType::Constraint | TokenType::Precision | TokenType::Comma | TokenType::Weight | TokenType::Semicolon | TokenType::LBrace | TokenType::RParen | TokenType::RBrack | TokenType::RBrace) {
+                            break;
+                        }
+                        self.advance();
+                    }
+                }
+            }
+        }
+
+        if failed {
+            return None
+        }
+        Some((c_mult_of, c_not_mult_of))
+    }
+
+    /*
+     * Parses a constraint. The pipe has already been consumed.
+     * We expect a NOT or a Constraint
+     * If we get a NOT, we expect a Constraint afterwards
+     */
+    fn parse_constraint(&mut self, pipe: &Token) -> Option<Constraint> {
+        let Some(constraint_token) = self.peek() else {
+            self.error(CompilerError::DanglingConstraint(pipe.line, pipe.column), pipe.span);
+            return None
+        };
+        let negated = match constraint_token.token_type {
             TokenType::Not => {
-                index += 1;
-                // TODO: Change with match if this ever gets expanded. Alternatively, refactor to avoid duplication
-                if tokens[index].token_type != TokenType::CMultOf {
-                    return (
-                        Err(CompilerError::UnexpectedToken(
-                            vec![TokenType::CMultOf],
-                            token.token_type.clone(),
-                            token.line,
-                            token.column
-                        )),
-                        index
-                    )
+                self.advance();
+                // TODO: Change with match if this ever gets expanded
+                let Some(negated_token) = self.peek() else {
+                    self.error(CompilerError::DanglingConstraint(pipe.line, pipe.column), pipe.span.to(self.previous_span()));
+                    return None
+                };
+                if negated_token.token_type != TokenType::CMultOf {
+                    self.error(CompilerError::UnexpectedToken(
+                        vec![TokenType::CMultOf],
+                        negated_token.token_type.clone(),
+                        negated_token.line,
+                        negated_token.column
+                    ), negated_token.span);
+                    return None
                 }
-                index += 1;
-                Constraint::NotMultipleOf(Vec::new())
+                self.advance();
+                true
             },
             TokenType::CMultOf => {
-                index += 1;
-                Constraint::MultipleOf(Decimal::ZERO)
+                self.advance();
+                false
             },
             _ => {
-                return (
-                    Err(CompilerError::UnexpectedToken(
-                        vec![TokenType::Not, TokenType::CMultOf],
-                        token.token_type.clone(),
-                        token.line,
-                        token.column
-                    )),
-                    index
-                )
+                self.error(CompilerError::UnexpectedToken(
+                    vec![TokenType::Not, TokenType::CMultOf],
+                    constraint_token.token_type.clone(),
+                    constraint_token.line,
+                    constraint_token.column
+                ), constraint_token.span);
+                return None
             }
         };
 
         // Find numbers
+        // We expect number, comma, number, comma..... After a number we could also have another constraint
         let mut entries: Vec<Decimal> = Vec::new();
-        let mut expecting_number = true; // we expect comma, number, comma, number..... After number we could also have another constraint
-
-        'loop1: loop {
-            if expecting_number {
-                if tokens[index].token_type != TokenType::Number {
-                    return (
-                        Err(CompilerError::UnexpectedToken(
-                            vec![TokenType::Number],
-                            token.token_type.clone(),
-                            token.line,
-                            token.column
-                        )),
-                        index
-                    )
-                }
+        loop {
+            // A constraint without values (or with a trailing comma) at the end of the program
+            let Some(number_token) = self.peek() else {
+                self.error(CompilerError::DanglingConstraint(pipe.line, pipe.column), pipe.span.to(self.previous_span()));
+                return None
+            };
+            // Multiples of a negative number are the same as the multiples of the positive one, the sign is ignored
+            let number_token = match number_token.token_type {
+                TokenType::Minus | TokenType::Plus => {
+                    self.advance();
+                    let Some(number_token) = self.peek() else {
+                        self.error(CompilerError::DanglingConstraint(pipe.line, pipe.column), pipe.span.to(self.previous_span()));
+                        return None
+                    };
+                    number_token
+                },
+                _ => number_token,
+            };
+            if number_token.token_type != TokenType::Number {
+                self.error(CompilerError::UnexpectedToken(
+                    vec![TokenType::Number],
+                    number_token.token_type.clone(),
+                    number_token.line,
+                    number_token.column
+                ), number_token.span);
+                return None
+            }
+            self.advance();
 
-                let number = Decimal::from_str_exact(&tokens[index].content).expect("lexing/parsing error. NaN found in numerical token");
-                entries.push(number);
-            } else {
-                if index >= tokens.len() {
-                    break;
+            let number = match parse_number(number_token) {
+                Ok(num) => num,
+                Err(error) => {
+                    self.error(error, number_token.span);
+                    return None
                 }
-                match tokens[index].token_type {
-                    TokenType::Comma => (), // Continue the loop as usual
-                    TokenType::Constraint => {
-                        break 'loop1;
-                    },
-                    _ => return (
-                        Err(CompilerError::UnexpectedToken(
+            };
+            // Every number is a multiple of 0, and nothing is a multiple of 0
+            if number.is_zero() {
+                self.error(CompilerError::InvalidConstraint(number, number_token.line, number_token.column), number_token.span);
+                return None
+            }
+            entries.push(number);
+
+            match self.peek() {
+                Some(token) => match token.token_type {
+                    TokenType::Comma => { self.advance(); }, // Continue the loop as usual
+                    TokenType::Constraint |
+                    TokenType::Precision |
+                    TokenType::Weight |
+                    TokenType::Semicolon |
+                    TokenType::LBrace |
+                    TokenType::RParen |
+                    TokenType::RBrack |
+                    TokenType::RBrace => break,
+                    // An operation with the constrained range
+                    TokenType::CMultOf |
+                    TokenType::Slash |
+                    TokenType::Percent |
+                    TokenType::Plus |
+                    TokenType::Minus => break,
+                    // Another expression after the constraint, the caller reports it
+                    TokenType::Number |
+                    TokenType::Dice |
+                    TokenType::Identifier |
+                    TokenType::LBrack |
+                    TokenType::LParen => break,
+                    _ => {
+                        self.error(CompilerError::UnexpectedToken(
                             vec![TokenType::Comma, TokenType::Constraint],
-                            tokens[index].token_type.clone(),
-                            tokens[index].line,
-                            tokens[index].column
-                        )),
-                        index
-                    )
-                }
+                            token.token_type.clone(),
+                            token.line,
+                            token.column
+                        ), token.span);
+                        return None
+                    }
+                },
+                None => break,
             }
-            expecting_number = !expecting_number;
-            index += 1;
         }
 
-        match &mut constraint {
-            Constraint::MultipleOf(lcm) => {
-                *lcm = least_common_multiple(&entries);
-                c_mult_of = Some(*lcm);
+        if negated {
+            Some(Constraint::NotMultipleOf(entries))
+        } else {
+            Some(Constraint::MultipleOf(least_common_multiple(&entries)))
+        }
+    }
+
+    /*
+     * Adds the constraints to the Gex. Constrained ranges that are small enough are precalculated.
+     * Reports constraints that leave no possible values in the range.
+     * The pipe is the first token of the constraints, used to report errors.
+     */
+    fn apply_constraints(&mut self, mut gex: Gex, c_mult_of: Option<Decimal>, c_not_mult_of: Option<Vec<Decimal>>, pipe: &Token) -> Option<Gex> {
+        match (c_mult_of, c_not_mult_of) {
+            (Some(mult_of), Some(not_mult_of)) => {
+                // Every multiple of the LCM is also a multiple of the numbers that divide it, so
+                // nothing could pass the NotMultipleOf constraint. No need to precalculate anything.
+                // Ranges that are too big to be precalculated are only checked this way.
+                let incompatible = not_mult_of.iter().any(|number| (mult_of % number).is_zero());
+                let possible_values = if incompatible {
+                    Ok(Vec::new())
+                } else {
+                    precalculate_constraint(mult_of, &not_mult_of, gex.min_number(), gex.max_number(), self.options.precalc_memory_budget)
+                };
+
+                match possible_values {
+                    Ok(values) if values.is_empty() => {
+                        self.error(CompilerError::EmptySupport(pipe.line, pipe.column), pipe.span.to(self.previous_span()));
+                        return None
+                    },
+                    Ok(values) => gex = Gex::from_precalc(gex, values),
+                    Err(max_byte_count) => {
+                        self.warning(
+                            CompilerWarning::PrecalculationSkipped(max_byte_count, self.options.precalc_memory_budget, pipe.line, pipe.column),
+                            pipe.span.to(self.previous_span())
+                        );
+                        gex.add_constraint(Constraint::MultipleOf(mult_of));
+                        gex.add_constraint(Constraint::NotMultipleOf(not_mult_of));
+                        gex.set_max_rerolls(self.options.max_rerolls);
+                    }
+                }
             },
-            Constraint::NotMultipleOf(items) => {
-                *items = entries;
-                c_not_mult_of = Some(items.clone());
+            (Some(mult_of), None) => gex.add_constraint(Constraint::MultipleOf(mult_of)),
+            (None, Some(not_mult_of)) => {
+                self.warning(
+                    CompilerWarning::RerolledConstraint(self.options.max_rerolls, pipe.line, pipe.column),
+                    pipe.span.to(self.previous_span())
+                );
+                gex.add_constraint(Constraint::NotMultipleOf(not_mult_of));
+                gex.set_max_rerolls(self.options.max_rerolls);
             },
+            (None, None) => {}
         }
+
+        Some(gex)
     }
 
-    // Precalc
-    if c_mult_of.is_some() && c_not_mult_of.is_some() {
-        let res = precalculate_constraint(gex.clone(), c_mult_of.unwrap(), c_not_mult_of.clone().unwrap(), gex.min_number(), gex.max_number());
-        if res.is_some() {
-            gex = res.unwrap()
-        } else {
-            gex.add_constraint(Constraint::MultipleOf(c_mult_of.unwrap()));
-            gex.add_constraint(Constraint::NotMultipleOf(c_not_mult_of.unwrap()));
+    // Helper functions
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.current)
+    }
+    fn check(&self, token_type: TokenType) -> bool {
+        self.peek().is_some_and(|token| token.token_type == token_type)
+    }
+    fn advance(&mut self) -> &'a Token {
+        let token = &self.tokens[self.current];
+        self.current += 1;
+        token
+    }
+    /*
+     * Consumes the closing delimiter of a sub-expression, selection, call or quantifier.
+     * If something else comes first, it's reported and skipped until the closing delimiter (`[1:5 2]`).
+     * The opening delimiter is only reported as unbalanced if the closing one is missing.
+     */
+    fn expect_closing(&mut self, opening: &Token, closing: TokenType) -> bool {
+        if self.check(closing.clone()) {
+            self.advance();
+            return true
+        }
+        match (self.peek(), self.find_closing(&closing)) {
+            (Some(token), Some(closing_index)) => {
+                self.error(CompilerError::UnexpectedToken(vec![closing], token.token_type.clone(), token.line, token.column), token.span);
+                self.current = closing_index + 1;
+            },
+            _ => self.error(CompilerError::UnbalancedDelimiter(opening.token_type.clone(), opening.line, opening.column), opening.span),
+        }
+        false
+    }
+    /*
+     * Index of the closing delimiter that matches a delimiter that is already open, skipping the pairs inside of it.
+     * None if a different closing delimiter or the end of the program comes first.
+     */
+    fn find_closing(&self, closing: &TokenType) -> Option<usize> {
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.token_type {
+                TokenType::LParen | TokenType::LBrack | TokenType::LBrace => depth += 1,
+                TokenType::RParen | TokenType::RBrack | TokenType::RBrace if depth > 0 => depth -= 1,
+                TokenType::RParen | TokenType::RBrack | TokenType::RBrace => {
+                    return (token.token_type == *closing).then_some(index)
+                },
+                _ => {}
+            }
         }
-    } else {
-        if let Some(mult_of) = c_mult_of {
-            gex.add_constraint(Constraint::MultipleOf(mult_of));
-        } else if let Some(not_mult_of) = c_not_mult_of {
-            gex.add_constraint(Constraint::NotMultipleOf(not_mult_of));
+        None
+    }
+    fn previous_span(&self) -> Span {
+        match self.current {
+            0 => Span::default(),
+            n => self.tokens[n-1].span,
+        }
+    }
+    /*
+     * Span of the next token. Empty span after the last token if there are no more.
+     */
+    fn current_span(&self) -> Span {
+        match self.peek() {
+            Some(token) => token.span,
+            None => {
+                let end = self.previous_span().end;
+                Span::new(end, end)
+            }
         }
     }
+    fn error(&mut self, error: CompilerError, span: Span) {
+        self.diagnostics.push(Diagnostic::error(error, span));
+    }
+    fn warning(&mut self, warning: CompilerWarning, span: Span) {
+        self.diagnostics.push(Diagnostic::warning(warning, span));
+    }
+}
 
-    (Ok(gex), index)
+fn starts_operand(token_type: &TokenType) -> bool {
+    is_range(token_type) || matches!(token_type, TokenType::Number | TokenType::Dice | TokenType::Identifier | TokenType::LBrack | TokenType::LParen | TokenType::Binding | TokenType::Param | TokenType::Minus | TokenType::Plus)
+}
+fn is_range(token_type: &TokenType) -> bool {
+    matches!(token_type, TokenType::RangeCC | TokenType::RangeOO | TokenType::RangeCO | TokenType::RangeOC)
 }
 
-fn find_subexpression_end(tokens: &[Token], mut index: usize) -> usize {
-    let mut counter: usize = 0;
-    // This first token should be a LParen, increment count by 1 if this is the case.
-    // When this first token is not a parenthesis we would exit immediately
-    if let TokenType::LParen = tokens[index].token_type {
-        counter += 1;
+/*
+ * Best-effort check for ranges that can never generate a number: X is always bigger than Y (`5..3`), X and Y are always
+ * the same and a bound is open (`0,,0`) or constant bounds don't have a multiple of the MultipleOf constraint between them
+ * (`0,,10|*10`). Other empty ranges are reported while generating (GenerateError::EmptyRange).
+ */
+fn is_certainly_empty(x: &Gex, y: &Gex, x_open: bool, y_open: bool, mult_of: Option<Decimal>) -> bool {
+    let (lowest, highest) = (x.min_number(), y.max_number());
+    if lowest > highest || ((x_open || y_open) && lowest >= highest) {
+        return true
     }
-
-    while counter > 0 {
-        index += 1;
-        // Increment counter when we encounter a new sub-expression,
-        // decrement it when we encounter the end of a sub-expression.
-        // If we reach 0 we found the end of our sub-expression.
-        match tokens[index].token_type {
-            TokenType::LParen => counter += 1,
-            TokenType::RParen => counter -= 1,
-            _ => {}
-        }
+    let constant = x.min_number() == x.max_number() && y.min_number() == y.max_number();
+    let (Some(mult_of), true) = (mult_of, constant) else {
+        return false
+    };
+    // Same as generating the range: the first and last multiples between the bounds
+    let (Some(first), Some(last)) = (lowest.checked_div(mult_of), highest.checked_div(mult_of)) else {
+        return false
+    };
+    let (mut first, mut last) = (first.ceil(), last.floor());
+    if x_open && first.checked_mul(mult_of) == Some(lowest) {
+        first += Decimal::ONE;
     }
+    if y_open && last.checked_mul(mult_of) == Some(highest) {
+        last -= Decimal::ONE;
+    }
+    last < first
+}
 
-    index
+/*
+ * Reads the content of a Number token.
+ * The lexer can produce tokens that aren't valid numbers (a lonely sign, numbers too big for a Decimal...)
+ */
+fn parse_number(token: &Token) -> Result<Decimal, CompilerError> {
+    Decimal::from_str_exact(&token.content)
+        .map_err(|_| CompilerError::InvalidNumber(token.content.clone(), token.line, token.column))
 }
-fn find_selection_end(tokens: &[Token], mut index: usize) -> usize {
-    let mut counter: usize = 0;
-    if let TokenType::LBrack = tokens[index].token_type {
-        counter += 1;
-    }
-    while counter > 0 {
-        index += 1;
-        match tokens[index].token_type {
-            TokenType::LBrack => counter += 1,
-            TokenType::RBrack => counter -= 1,
-            _ => {}
-        }
+
+/*
+ * Reads the content of a Dice token (`3d6`, `d20`, `2d10!`, `4d6kh3`, `4d6dl1`).
+ * Exploding dice are rolled again every time they get their highest value, `gex::dice::MAX_EXPLOSIONS` times at most.
+ * There has to be between 1 and MAX_DICE dice with between 1 and MAX_DICE sides, dice kept must be between 1 and
+ * the amount of dice and dice with one side can't explode.
+ */
+fn parse_dice(token: &Token) -> Result<Dice, CompilerError> {
+    let invalid = || CompilerError::InvalidDice(token.content.clone(), token.line, token.column);
+
+    let (count, rest) = token.content.split_once('d').ok_or_else(invalid)?;
+    let count: u32 = if count.is_empty() { 1 } else { count.parse().map_err(|_| invalid())? };
+    let sides_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let sides: u32 = rest[..sides_end].parse().map_err(|_| invalid())?;
+    // `!` can be before or after the dice kept (`5d10!kh2`, `5d10kh2!`), but only once
+    let exploding = match rest.matches('!').count() {
+        0 => false,
+        1 => true,
+        _ => return Err(invalid()),
+    };
+    let rest = rest[sides_end..].replace('!', "");
+    let rest = rest.as_str();
+
+    // The amount of dice kept or dropped is 1 if it's omitted (`2d20kh`)
+    let amount = |digits: &str| if digits.is_empty() { Some(1) } else { digits.parse::<u32>().ok() };
+    let keep = match rest.split_at(rest.len().min(2)) {
+        ("", _) => None,
+        ("kh", digits) => Some(Keep::Highest(amount(digits).ok_or_else(invalid)?)),
+        ("kl", digits) => Some(Keep::Lowest(amount(digits).ok_or_else(invalid)?)),
+        ("dh", digits) => Some(Keep::Lowest(count.checked_sub(amount(digits).ok_or_else(invalid)?).ok_or_else(invalid)?)),
+        ("dl", digits) => Some(Keep::Highest(count.checked_sub(amount(digits).ok_or_else(invalid)?).ok_or_else(invalid)?)),
+        _ => return Err(invalid()),
+    };
+
+    let kept = match keep {
+        Some(Keep::Highest(kept)) | Some(Keep::Lowest(kept)) => kept,
+        None => count,
+    };
+    if !(1..=MAX_DICE).contains(&count) || !(1..=MAX_DICE).contains(&sides) || kept == 0 || kept > count || (exploding && sides == 1) {
+        return Err(invalid())
     }
-    index
+    Ok(Dice::new(count, sides, exploding, keep))
+}
+
+/*
+ * Reads the content of a Precision token and returns the amount of decimals.
+ * `:int` is the same as `@0`
+ */
+fn parse_precision(token: &Token) -> Result<u32, CompilerError> {
+    let scale = match token.content.as_str() {
+        ":int" => Some(0),
+        content => content
+            .strip_prefix('@')
+            .and_then(|decimals| decimals.parse::<u32>().ok())
+            .filter(|decimals| *decimals <= Decimal::MAX_SCALE),
+    };
+    scale.ok_or_else(|| CompilerError::InvalidPrecision(token.content.clone(), token.line, token.column))
 }
 
 fn least_common_multiple(numbers: &[Decimal]) -> Decimal {
     // Return this number if the length is 1
-    if numbers.len() == 0 { panic!("LCM of 0 numbers?? WTF?") }
+    if numbers.is_empty() { panic!("LCM of 0 numbers?? WTF?") }
     if numbers.len() == 1 { return numbers[0] }
 
     let half_len = numbers.len() / 2;
@@ -430,23 +1190,23 @@ fn greatest_common_denominator(x: Decimal, y: Decimal) -> Decimal {
 }
 
 /**
- * Returns None if the range is too big for the budget or if there are no possible values
+ * Returns every possible value between start and end (sorted).
+ * Returns the amount of bytes it would need if the range is too big for the budget
  */
-fn precalculate_constraint(orig: Gex, multiple_of: Decimal, not_multiple_of: Vec<Decimal>, start: Decimal, end: Decimal) -> Option<Gex> {
-    let max_byte_count = calc_max_constraint_size(multiple_of, end - start);
-    println!("MAX MEMORY: {}/{} bytes", max_byte_count, PRECALC_MEMORY_BUDGET);
-    if max_byte_count > PRECALC_MEMORY_BUDGET {
-        println!("Warning: Max byte count for this range is {}. Budget is {} bytes", max_byte_count, PRECALC_MEMORY_BUDGET);
-        return None
+fn precalculate_constraint(multiple_of: Decimal, not_multiple_of: &[Decimal], start: Decimal, end: Decimal, memory_budget: usize) -> Result<Vec<Decimal>, Decimal> {
+    let max_byte_count = calc_max_constraint_size(multiple_of, start, end);
+    if max_byte_count > Decimal::from(memory_budget) {
+        return Err(max_byte_count)
     }
 
     let mut possible_value: Vec<Decimal> = Vec::new();
-    let mut current = (start / multiple_of).floor() * multiple_of;
+    // First multiple within the range
+    let mut current = (start / multiple_of).ceil() * multiple_of;
 
     while current <= end  {
         // Check for blacklisted multiples
         let mut is_mult = false;
-        for number in &not_multiple_of {
+        for number in not_multiple_of {
             if current % number == Decimal::ZERO { is_mult = true; }
         }
         // If it's within the constraint we're done
@@ -456,15 +1216,13 @@ fn precalculate_constraint(orig: Gex, multiple_of: Decimal, not_multiple_of: Vec
         current += multiple_of;
     }
 
-    if possible_value.is_empty() {
-        // TODO: Add proper error handling
-        panic!("Incompatible constraints detected, check your constraints.")
-    }
-
-    let gex = Gex::from_precalc(orig, possible_value);
-    Some(gex)
+    Ok(possible_value)
 }
 
-fn calc_max_constraint_size(multiple_of: Decimal, range: Decimal) -> Decimal {
-    (range / multiple_of) * Decimal::from_usize(size_of::<Decimal>()).unwrap()
-}
\ No newline at end of file
+// Ranges too big to calculate their size (like the ones with unbounded parameters) use the biggest size possible
+fn calc_max_constraint_size(multiple_of: Decimal, start: Decimal, end: Decimal) -> Decimal {
+    end.checked_sub(start)
+        .and_then(|range| range.checked_div(multiple_of))
+        .and_then(|count| count.checked_mul(Decimal::from_usize(size_of::<Decimal>()).unwrap()))
+        .unwrap_or(Decimal::MAX)
+}
@@ -0,0 +1,160 @@
+use std::{fmt::Debug, rc::Rc};
+
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::{diagnostic::Diagnostic, gex::registry::Registry};
+#[cfg(target_arch = "wasm32")]
+use super::gex::registry::Arity;
+
+pub(crate) const DEFAULT_PRECALC_MEMORY_BUDGET: usize = 131072; // 128 KB
+pub(crate) const DEFAULT_MAX_REROLLS: usize = 1000;
+
+/// Function called with every warning found in a program that compiled successfully
+pub type WarningHandler = Rc<dyn Fn(&Diagnostic)>;
+
+/// A parameter that expressions can use (`{level}`). Its value is supplied when generating numbers.
+///
+/// The bounds are the smallest and biggest values the parameter can have. They are used to
+/// know the bounds of the expressions that use it, like precalculated constraints do.
+/// Values outside of the bounds are still accepted
+#[derive(Debug, Clone)]
+pub struct ParamDeclaration {
+    pub name: String,
+    pub min: Decimal,
+    pub max: Decimal,
+}
+
+impl ParamDeclaration {
+    /// A parameter that can have any value
+    pub fn new(name: &str) -> Self {
+        ParamDeclaration { name: name.to_owned(), min: Decimal::MIN, max: Decimal::MAX }
+    }
+    pub fn bounded(name: &str, min: Decimal, max: Decimal) -> Self {
+        ParamDeclaration { name: name.to_owned(), min, max }
+    }
+}
+
+/// Configuration for the compiler.
+///
+/// ```
+/// use grand::CompileOptions;
+///
+/// let options = CompileOptions {
+///     strict: true,
+///     max_rerolls: 50,
+///     ..Default::default()
+/// };
+/// let gex = grand::compile_raw_with("0..10|*1|!*2", &options).unwrap();
+/// assert!(gex.generate() <= 10.into());
+/// ```
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct CompileOptions {
+    /// Maximum amount of bytes a constrained range can use to store its precalculated values.
+    /// Ranges that need more memory generate numbers and reroll them until they satisfy the constraints
+    pub precalc_memory_budget: usize,
+    /// Maximum amount of times a number can be rerolled when it doesn't satisfy the constraints
+    pub max_rerolls: usize,
+    /// Unknown characters are errors instead of being ignored
+    pub strict: bool,
+    /// Value used when the start of a range is omitted (`..10`)
+    #[wasm_bindgen(skip)]
+    pub range_min: Decimal,
+    /// Value used when the end of a range is omitted (`0..`)
+    #[wasm_bindgen(skip)]
+    pub range_max: Decimal,
+    /// Receives the warnings of the program. Nothing is printed by default
+    #[wasm_bindgen(skip)]
+    pub on_warning: Option<WarningHandler>,
+    /// Parameters the program can use. Using parameters that aren't declared is an error
+    #[wasm_bindgen(skip)]
+    pub params: Vec<ParamDeclaration>,
+    /// Functions the program can call besides the built-in ones (`price(10..20)`)
+    #[wasm_bindgen(skip)]
+    pub functions: Registry,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            precalc_memory_budget: DEFAULT_PRECALC_MEMORY_BUDGET,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            strict: false,
+            range_min: i64::MIN.into(),
+            range_max: i64::MAX.into(),
+            on_warning: None,
+            params: Vec::new(),
+            functions: Registry::new(),
+        }
+    }
+}
+
+impl Debug for CompileOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompileOptions")
+            .field("precalc_memory_budget", &self.precalc_memory_budget)
+            .field("max_rerolls", &self.max_rerolls)
+            .field("strict", &self.strict)
+            .field("range_min", &self.range_min)
+            .field("range_max", &self.range_max)
+            .field("on_warning", &self.on_warning.is_some())
+            .field("params", &self.params)
+            .field("functions", &self.functions)
+            .finish()
+    }
+}
+
+#[wasm_bindgen]
+impl CompileOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the values used when the start or end of a range is omitted.
+    /// Values that can't be represented are ignored
+    pub fn set_range_bounds(&mut self, min: f64, max: f64) {
+        if let Some(min) = Decimal::from_f64(min) {
+            self.range_min = min;
+        }
+        if let Some(max) = Decimal::from_f64(max) {
+            self.range_max = max;
+        }
+    }
+
+    /// Declares a parameter that can have any value
+    pub fn declare_param(&mut self, name: &str) {
+        self.params.push(ParamDeclaration::new(name));
+    }
+    /// Declares a parameter with the smallest and biggest values it can have.
+    /// Bounds that can't be represented are ignored
+    pub fn declare_bounded_param(&mut self, name: &str, min: f64, max: f64) {
+        let mut param = ParamDeclaration::new(name);
+        if let Some(min) = Decimal::from_f64(min) {
+            param.min = min;
+        }
+        if let Some(max) = Decimal::from_f64(max) {
+            param.max = max;
+        }
+        self.params.push(param);
+    }
+}
+
+// JavaScript functions can only be registered in WASM
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl CompileOptions {
+    /// Registers a JavaScript function that accepts between `min_args` and `max_args` arguments and can give any result.
+    /// It's called with the numbers generated by its arguments (`price(10..20)` calls `price(12.5)`) and must return a number
+    pub fn register_function(&mut self, name: &str, function: js_sys::Function, min_args: usize, max_args: usize) {
+        self.functions.register_js(name, Arity { min: min_args, max: max_args }, Decimal::MIN, Decimal::MAX, function);
+    }
+    /// Registers a JavaScript function with the smallest and biggest results it can give.
+    /// Bounds that can't be represented are ignored
+    pub fn register_bounded_function(&mut self, name: &str, function: js_sys::Function, min_args: usize, max_args: usize, min: f64, max: f64) {
+        let min = Decimal::from_f64(min).unwrap_or(Decimal::MIN);
+        let max = Decimal::from_f64(max).unwrap_or(Decimal::MAX);
+        self.functions.register_js(name, Arity { min: min_args, max: max_args }, min, max, function);
+    }
+}
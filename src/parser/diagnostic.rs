@@ -0,0 +1,197 @@
+use std::{error::Error, fmt::Display};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use super::{parse_error::CompilerError, parse_warning::CompilerWarning};
+
+/// Byte offsets of a piece of the source code.
+/// `start` is inclusive and `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns a span that covers both spans and everything in between
+    pub fn to(self, other: Span) -> Self {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// How bad a diagnostic is. Errors make the compilation fail, warnings don't.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// What was found, an error or a warning
+#[derive(Debug, Clone)]
+pub enum DiagnosticKind {
+    Error(CompilerError),
+    Warning(CompilerWarning),
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticKind::Error(error) => write!(f, "{}", error),
+            DiagnosticKind::Warning(warning) => write!(f, "{}", warning),
+        }
+    }
+}
+
+/// A problem found while compiling a Grand Expression and the part of the
+/// source code that caused it.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    kind: DiagnosticKind,
+    span: Span,
+}
+
+impl Diagnostic {
+    pub fn error(error: CompilerError, span: Span) -> Self {
+        Diagnostic { kind: DiagnosticKind::Error(error), span }
+    }
+    pub fn warning(warning: CompilerWarning, span: Span) -> Self {
+        Diagnostic { kind: DiagnosticKind::Warning(warning), span }
+    }
+
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Prints the message followed by the line of the source code where the problem is.
+    /// The problematic part is underlined with carets:
+    ///
+    /// ```text
+    /// error: Dangling constraint in line 0, column 6. Expected a constraint type followed by its values
+    ///  0 | 0..10|*
+    ///    |      ^^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let severity = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        // Find the line that contains the beginning of the span
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[start..].find('\n').map(|i| start + i).unwrap_or(source.len());
+        let line_number = source[..line_start].matches('\n').count();
+        let line = &source[line_start..line_end];
+
+        // Spans that continue in the next lines are only underlined until the end of this line.
+        // Empty spans (missing code) still get a caret.
+        let end = self.span.end.clamp(start, line_end);
+        let padding = source[line_start..start].chars().count();
+        let underline = source[start..end].chars().count().max(1);
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        format!(
+            "{severity}: {}\n {line_number} | {line}\n {gutter} | {}{}",
+            self.kind,
+            " ".repeat(padding),
+            "^".repeat(underline)
+        )
+    }
+}
+
+#[wasm_bindgen]
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            DiagnosticKind::Error(_) => Severity::Error,
+            DiagnosticKind::Warning(_) => Severity::Warning,
+        }
+    }
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+    /// Byte offset where the problem starts
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+    /// Byte offset where the problem ends (exclusive)
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+}
+
+/// Every problem found while compiling a Grand Expression.
+///
+/// Printing it shows every diagnostic with the source code underlined.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    source: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(source: &str, diagnostics: Vec<Diagnostic>) -> Self {
+        Diagnostics { source: source.to_owned(), diagnostics }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity() == Severity::Error)
+    }
+    /// The source code these diagnostics refer to
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render(&self) -> String {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&self.source))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.iter()
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+impl Error for Diagnostics {}
+
+// Compilation errors are thrown to JavaScript as an array of Diagnostic objects
+impl From<Diagnostics> for JsValue {
+    fn from(diagnostics: Diagnostics) -> Self {
+        JsValue::from(diagnostics.diagnostics)
+    }
+}
@@ -1,14 +1,41 @@
+use std::sync::Arc;
+
+use aggregate::{Aggregate, ListBounds};
+use alias_table::AliasTable;
+use binary_operator::BinaryOperator;
+use combinator::Combinator;
 use constraint::Constraint;
+use dice::Dice;
+use eval_context::EvalContext;
+use function::Function;
+use params::Params;
+use registry::HostFunction;
 use expression::Expression;
-use rust_decimal::{dec, Decimal};
+use generate_error::GenerateError;
+use rust_decimal::{prelude::ToPrimitive, Decimal, RoundingStrategy};
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use crate::rng_functions::{random_usize, random_decimal};
+use crate::rng_functions::{random_decimal, random_decimal_int, random_usize};
+use crate::rng_sources::OsRng;
+use crate::rng_traits::GrandRng;
+
+use super::compile_options::DEFAULT_MAX_REROLLS;
 
 pub mod expression;
 pub mod constraint;
+pub mod binary_operator;
+pub mod combinator;
+pub mod dice;
+pub mod aggregate;
+pub mod function;
+pub mod alias_table;
+mod eval_context;
+pub mod params;
+pub mod registry;
+pub mod generate_error;
 
-const MAX_RANGE_HELL_REROLLS: usize = 1000;
+/// Maximum amount of times a quantifier can repeat an expression (`(1..6){3}`)
+pub const MAX_REPETITIONS: usize = 1_000_000;
 
 /// A Grand Expression. It is a recursive structure that evaluates a range and modifiers (constraints)
 /// or a selection from a list.
@@ -21,6 +48,8 @@ pub struct Gex {
     max_number: Decimal,
     dynamic_constraints: Vec<Constraint>,
     mult_of: Option<Decimal>, // For mult_of constraints. Allows the generator to generate closed/open ranges properly. It's the scale of the mult_of constraint's decimal
+    max_rerolls: usize, // For NotMultipleOf constraints. Maximum amount of attempts to get a valid number
+    precision: Option<u32>, // Amount of decimals of the generated numbers (`@3`). `:int` is 0
 }
 
 impl Gex {
@@ -30,7 +59,9 @@ impl Gex {
             min_number: num,
             max_number: num,
             dynamic_constraints: Vec::new(),
-            mult_of: None
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
         }
     }
     pub fn from_range(x: Gex, y: Gex, x_open: bool, y_open: bool) -> Self {
@@ -46,7 +77,9 @@ impl Gex {
             min_number,
             max_number,
             dynamic_constraints: Vec::new(),
-            mult_of: None
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
         }
     }
     pub fn from_select(objects: Vec<Gex>) -> Self {
@@ -61,22 +94,214 @@ impl Gex {
             .reduce(|acc, elem| (Decimal::min(acc.0, elem.0), Decimal::max(acc.1, elem.1)))
             .expect("range was empty. This should be an Error, not a Panic");
 
-        let objects: Vec<Box<Gex>> = objects.iter().map(|obj| {
-            Box::new(obj.to_owned())
-        }).collect();
+        Gex {
+            expression_type: Expression::Select(objects, None),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Creates a selection where every entry is picked with a probability proportional to its weight.
+    /// Entries with a weight of zero are never picked, so they don't affect the bounds.
+    ///
+    /// Weights must be non-negative and at least one of them must be greater than zero.
+    /// Returns None if they can't be compared exactly (too big or with too many decimals)
+    pub fn from_weighted_select(objects: Vec<Gex>, weights: &[Decimal]) -> Option<Self> {
+        let alias_table = AliasTable::new(weights)?;
+        let (min_number, max_number) = objects
+            .iter()
+            .zip(weights)
+            .filter(|(_, weight)| !weight.is_zero())
+            .map(|(gex, _)| (gex.min_number, gex.max_number))
+            .reduce(|acc, elem| (Decimal::min(acc.0, elem.0), Decimal::max(acc.1, elem.1)))?;
+
+        Some(Gex {
+            expression_type: Expression::Select(objects, Some(alias_table)),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        })
+    }
+    /// Creates a selection where every entry is picked with a probability proportional to the size of its support, so
+    /// the result is (roughly) uniform over all the entries together (`[0..1, 10..1000]|uniform`).
+    ///
+    /// If every entry generates a countable amount of values (numbers, ranges with constant constraints or precision)
+    /// they are weighted by that amount. Otherwise they are weighted by the length between their bounds, so
+    /// single numbers are never picked next to a range.
+    /// Entries that overlap are counted twice.
+    pub fn from_uniform_select(objects: Vec<Gex>) -> Self {
+        let weights: Vec<Decimal> = objects
+            .iter()
+            .map(|gex| match gex.support() {
+                Support::Count(count) => Some(count),
+                Support::Uncountable => None,
+            })
+            .collect::<Option<Vec<Decimal>>>()
+            .unwrap_or_else(|| objects.iter().map(Gex::length).collect());
+
+        // Very different weights may not fit in the alias table, they're rounded until they do
+        let weights = std::iter::once(weights.clone())
+            .chain((0..=Decimal::MAX_SCALE).rev().map(|scale| {
+                weights.iter().map(|weight| weight.round_dp(scale)).collect()
+            }))
+            .find(|weights: &Vec<Decimal>| AliasTable::new(weights).is_some());
+
+        match weights {
+            Some(weights) => Gex::from_weighted_select(objects, &weights)
+                .expect("weights were checked. This should be an Error, not a Panic"),
+            None => Gex::from_select(objects),
+        }
+    }
+    /// Creates an arithmetic operation between the results of two expressions.
+    /// Operations between two numbers are calculated while compiling.
+    pub fn from_binary(operator: BinaryOperator, x: Gex, y: Gex) -> Self {
+        if let (Expression::Number(x_num), Expression::Number(y_num), None, None) = (&x.expression_type, &y.expression_type, x.precision, y.precision) {
+            if let Some(result) = operator.apply(*x_num, *y_num) {
+                return Gex::from_num(result)
+            }
+        }
+        let (min_number, max_number) = operator.bounds((x.min_number, x.max_number), (y.min_number, y.max_number));
 
         Gex {
-            expression_type: Expression::Select(objects),
+            expression_type: Expression::BinaryOp(operator, Box::new(x), Box::new(y)),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Creates an expression that generates the value of every binding (once) and then generates the body,
+    /// which can use these values.
+    /// Definitions can use the bindings defined before them.
+    pub fn from_bindings(definitions: Vec<Gex>, body: Gex) -> Self {
+        Gex {
+            min_number: body.min_number,
+            max_number: body.max_number,
+            expression_type: Expression::Bindings(definitions, Box::new(body)),
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /*
+     * Uses the value of a binding, `index` is the position of its definition.
+     * The bounds are the ones of the definition.
+     * Only the parser creates these, inside of the Bindings that define them, so the index always exists when evaluating
+     */
+    pub(crate) fn from_binding_ref(index: usize, min_number: Decimal, max_number: Decimal) -> Self {
+        Gex {
+            expression_type: Expression::BindingRef(index),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Rolls dice (`3d6`, `4d6kh3`). The bounds are the smallest and biggest possible rolls
+    pub fn from_dice(dice: Dice) -> Self {
+        let (min_number, max_number) = dice.bounds();
+        Gex {
+            expression_type: Expression::Dice(dice),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Repeats an expression to generate several numbers (`(1..6){3}`). The amount of repetitions is `min_count`,
+    /// or a random integer between `min_count` and `max_count` (both included) if there is a maximum.
+    /// The bounds are the ones of every number generated
+    pub fn from_repeat(gex: Gex, min_count: Gex, max_count: Option<Gex>) -> Self {
+        Gex {
+            min_number: gex.min_number,
+            max_number: gex.max_number,
+            expression_type: Expression::Repeat(Box::new(gex), Box::new(min_count), max_count.map(Box::new)),
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Combines the numbers generated by the arguments into one (`sum((1..6){3})`, `max(0..10, 5..20)`).
+    /// Every argument adds one number to the list, or one per repetition if it has a quantifier.
+    /// Selections without weights add all of their entries (`sum([1..3, 5..8])`)
+    pub fn from_aggregate(aggregate: Aggregate, args: Vec<Gex>) -> Self {
+        let parts: Vec<ListBounds> = args.iter().flat_map(Gex::list_bounds).collect();
+        let (min_number, max_number) = aggregate.bounds(&parts);
+        Gex {
+            expression_type: Expression::Aggregate(aggregate, args),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Calls a built-in function (`abs(-5..5)`, `round(0..10, 2)`, `pow(2, 0..10)`).
+    /// The bounds are the smallest and biggest results the function can give for the bounds of its arguments
+    pub fn from_function(function: Function, args: Vec<Gex>) -> Self {
+        let bounds: Vec<(Decimal, Decimal)> = args.iter().map(|gex| (gex.min_number, gex.max_number)).collect();
+        let (min_number, max_number) = function.bounds(&bounds);
+        Gex {
+            expression_type: Expression::Call(function, args),
             min_number,
             max_number,
             dynamic_constraints: Vec::new(),
-            mult_of: None
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
         }
     }
+    /// Calls a function registered by the application (`price(10..20)`).
+    /// The bounds are the ones declared for the function
+    pub fn from_host(function: HostFunction, args: Vec<Gex>) -> Self {
+        let (min_number, max_number) = function.bounds();
+        Gex {
+            expression_type: Expression::Host(function, args),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Uses the value of a parameter, supplied when generating numbers.
+    /// The bounds are the ones declared for the parameter
+    pub fn from_param(name: &str, min_number: Decimal, max_number: Decimal) -> Self {
+        Gex {
+            expression_type: Expression::Param(name.to_owned()),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision: None,
+        }
+    }
+    /// Creates a range that picks from the sorted list of values that satisfy its constraints.
+    /// The compiler reports constraints without possible values (`CompilerError::EmptySupport`) before calling this.
+    ///
+    /// Panics if `values` is empty.
     pub fn from_precalc(orig: Gex, values: Vec<Decimal>) -> Self {
         // Get minimum and maximum values
-        let min = values.get(0).unwrap().clone();
-        let max = values.get(values.len()-1).unwrap().clone();
+        let min = *values.first().expect("precalculated ranges need at least one possible value");
+        let max = *values.last().expect("precalculated ranges need at least one possible value");
+        let precision = orig.precision;
         let (x_gex, y_gex, x_open, y_open) = if let Expression::Range(xg, yg, xo, yo) = orig.expression_type {
             (xg, yg, xo, yo)
         } else {
@@ -88,7 +313,9 @@ impl Gex {
             min_number: min,
             max_number: max,
             dynamic_constraints: Vec::new(),
-            mult_of: None
+            mult_of: None,
+            max_rerolls: DEFAULT_MAX_REROLLS,
+            precision,
         }
     }
 
@@ -99,6 +326,108 @@ impl Gex {
         self.max_number
     }
 
+    pub fn precision(&self) -> Option<u32> {
+        self.precision
+    }
+
+    /// Whether the expression generates several numbers (it has a quantifier). These expressions
+    /// are generated with `generate_many()`
+    pub fn is_repeated(&self) -> bool {
+        match &self.expression_type {
+            Expression::Repeat(..) => true,
+            Expression::Bindings(_, body) => body.is_repeated(),
+            _ => false,
+        }
+    }
+
+    /// Sets the amount of decimals of the generated numbers. Ranges generate numbers with this
+    /// amount of decimals, other numbers are rounded.
+    /// Entries in selections and repeated expressions that don't have a precision use this one too
+    pub fn set_precision(&mut self, scale: u32) {
+        self.precision = Some(scale);
+        match &self.expression_type {
+            // Ranges with constant bounds generate the numbers with this precision between their bounds (the bounds
+            // are rounded inwards). The minimum is bigger than the maximum if there are none (`0.2..0.8:int`)
+            Expression::Range(x, y, x_open, y_open) if self.mult_of.is_none() && x.min_number == x.max_number && y.min_number == y.max_number => {
+                let step = Decimal::new(1, scale);
+                let (x, y) = (x.min_number, y.max_number);
+                self.min_number = x.round_dp_with_strategy(scale, RoundingStrategy::ToPositiveInfinity);
+                self.max_number = y.round_dp_with_strategy(scale, RoundingStrategy::ToNegativeInfinity);
+                if *x_open && self.min_number == x {
+                    self.min_number = self.min_number.saturating_add(step);
+                }
+                if *y_open && self.max_number == y {
+                    self.max_number = self.max_number.saturating_sub(step);
+                }
+            },
+            // Rounding never changes the order of two numbers, so the rounded bounds are the bounds of the rounded numbers
+            _ => {
+                self.min_number = self.min_number.round_dp_with_strategy(scale, RoundingStrategy::MidpointNearestEven);
+                self.max_number = self.max_number.round_dp_with_strategy(scale, RoundingStrategy::MidpointNearestEven);
+            },
+        }
+        match &mut self.expression_type {
+            Expression::Select(items, _) => {
+                for item in items.iter_mut().filter(|item| item.precision.is_none()) {
+                    item.set_precision(scale);
+                }
+            },
+            Expression::Repeat(gex, _, _) if gex.precision.is_none() => gex.set_precision(scale),
+            _ => {},
+        }
+    }
+
+    /// Sets the maximum amount of times a number can be rerolled when it doesn't satisfy the constraints
+    pub fn set_max_rerolls(&mut self, max_rerolls: usize) {
+        self.max_rerolls = max_rerolls;
+    }
+
+    /// Rerolls the numbers generated by the expression until one of them satisfies the predicate, for constraints
+    /// that can't be written in the expression (like "not a prime"). Returns `GenerateError::RerollsExhausted` after
+    /// the maximum amount of rerolls of the expression (`CompileOptions::max_rerolls`, see `set_max_rerolls()`).
+    /// The bounds don't change.
+    ///
+    /// Filters and maps work on expressions that generate a single number.
+    ///
+    /// ```
+    /// use grand::GenerateError;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let gex = grand::compile_raw("1..100:int").unwrap().filter(|number| number % Decimal::TWO == Decimal::ONE);
+    /// assert_eq!(gex.generate() % Decimal::TWO, Decimal::ONE);
+    ///
+    /// let gex = grand::compile_raw("1..100:int").unwrap().filter(|number| *number > 1000.into());
+    /// assert!(matches!(gex.try_generate(), Err(GenerateError::RerollsExhausted(_))));
+    /// ```
+    pub fn filter(self, predicate: impl Fn(&Decimal) -> bool + Send + Sync + 'static) -> Gex {
+        let (min_number, max_number) = (self.min_number, self.max_number);
+        self.wrap(Combinator::Filter(Arc::new(predicate)), min_number, max_number)
+    }
+    /// Transforms the numbers generated by the expression. The bounds of the result are unknown,
+    /// so they are the limits of a Decimal
+    ///
+    /// ```
+    /// use rust_decimal::Decimal;
+    ///
+    /// let gex = grand::compile_raw("1..6:int").unwrap().map(|number| number * Decimal::TEN);
+    /// assert_eq!(gex.generate() % Decimal::TEN, Decimal::ZERO);
+    /// ```
+    pub fn map(self, function: impl Fn(Decimal) -> Decimal + Send + Sync + 'static) -> Gex {
+        self.wrap(Combinator::Map(Arc::new(function)), Decimal::MIN, Decimal::MAX)
+    }
+    // Filters reroll as many times as the wrapped expression
+    fn wrap(self, combinator: Combinator, min_number: Decimal, max_number: Decimal) -> Gex {
+        Gex {
+            max_rerolls: self.max_rerolls,
+            expression_type: Expression::Combinator(Box::new(self), combinator),
+            min_number,
+            max_number,
+            dynamic_constraints: Vec::new(),
+            mult_of: None,
+            precision: None,
+        }
+    }
+
     pub fn add_constraint(&mut self, constraint: Constraint) {
         if let Constraint::MultipleOf(value) = constraint {
             self.mult_of = Some(value);
@@ -106,102 +435,408 @@ impl Gex {
         self.dynamic_constraints.push(constraint);
     }
 
+    /// Generates a random number.
+    ///
+    /// Panics if the number can't be generated, use `try_generate()` to handle these cases.
     pub fn generate(&self) -> Decimal {
+        self.generate_with(&mut OsRng)
+    }
+
+    /// Generates a random number using the given source of randomness.
+    /// Using a `SeededRng` with the same seed always generates the same numbers.
+    ///
+    /// Panics if the number can't be generated, use `try_generate_with()` to handle these cases.
+    pub fn generate_with(&self, rng: &mut impl GrandRng) -> Decimal {
+        self.try_generate_with(rng).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Generates a random number. Returns an error instead of a wrong number when the constraints
+    /// aren't satisfied after the maximum amount of rerolls, when the bounds generated by sub-expressions
+    /// don't contain any possible value or when a number can't be represented.
+    ///
+    /// ```
+    /// let gex = grand::compile_raw("(0..5)..(10..15)|*2|!*3").unwrap();
+    /// let number = gex.try_generate().unwrap();
+    /// assert!(number >= 0.into() && number <= 15.into());
+    ///
+    /// // The bounds generated by the sub-expressions never have a multiple of 10 between them
+    /// let gex = grand::compile_raw("(1..2)..(3..4)|*10").unwrap();
+    /// assert!(gex.try_generate().is_err());
+    /// ```
+    pub fn try_generate(&self) -> Result<Decimal, GenerateError> {
+        self.try_generate_with(&mut OsRng)
+    }
+
+    /// Same as `try_generate()` but using the given source of randomness
+    pub fn try_generate_with(&self, rng: &mut impl GrandRng) -> Result<Decimal, GenerateError> {
+        self.eval(&mut EvalContext::new(rng, None))
+    }
+
+    /// Generates a random number using the given values for the parameters of the expression (`{level}`).
+    ///
+    /// Panics if the number can't be generated or if a parameter is missing, use `try_generate_with_params()`
+    /// to handle these cases.
+    pub fn generate_with_params(&self, params: &Params) -> Decimal {
+        self.try_generate_with_params(params).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `try_generate()` but using the given values for the parameters of the expression.
+    /// Returns `GenerateError::MissingParam` if one of them is missing
+    pub fn try_generate_with_params(&self, params: &Params) -> Result<Decimal, GenerateError> {
+        self.try_generate_with_rng_and_params(&mut OsRng, params)
+    }
+
+    /// Same as `try_generate_with_params()` but using the given source of randomness
+    pub fn try_generate_with_rng_and_params(&self, rng: &mut impl GrandRng, params: &Params) -> Result<Decimal, GenerateError> {
+        self.eval(&mut EvalContext::new(rng, Some(params)))
+    }
+
+    /// Generates a list of random numbers. Expressions with a quantifier (`(1..6){3}`) generate one number
+    /// per repetition, the rest of them generate a single number.
+    /// Bindings are generated once for the whole list.
+    ///
+    /// ```
+    /// let gex = grand::compile_raw("(1..6:int){3}").unwrap();
+    /// let rolls = gex.generate_many();
+    /// assert_eq!(rolls.len(), 3);
+    /// assert!(rolls.iter().all(|roll| *roll >= 1.into() && *roll <= 6.into()));
+    /// ```
+    ///
+    /// Panics if a number can't be generated, use `try_generate_many()` to handle these cases.
+    pub fn generate_many(&self) -> Vec<Decimal> {
+        self.generate_many_with(&mut OsRng)
+    }
+
+    /// Same as `generate_many()` but using the given source of randomness
+    pub fn generate_many_with(&self, rng: &mut impl GrandRng) -> Vec<Decimal> {
+        self.try_generate_many_with(rng).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `generate_many()` but returns an error instead of panicking (see `try_generate()`)
+    pub fn try_generate_many(&self) -> Result<Vec<Decimal>, GenerateError> {
+        self.try_generate_many_with(&mut OsRng)
+    }
+
+    /// Same as `try_generate_many()` but using the given source of randomness
+    pub fn try_generate_many_with(&self, rng: &mut impl GrandRng) -> Result<Vec<Decimal>, GenerateError> {
+        let mut numbers = Vec::new();
+        self.eval_many(&mut EvalContext::new(rng, None), &mut numbers)?;
+        Ok(numbers)
+    }
+
+    /// Same as `try_generate_many()` but using the given source of randomness and values for the parameters
+    pub fn try_generate_many_with_rng_and_params(&self, rng: &mut impl GrandRng, params: &Params) -> Result<Vec<Decimal>, GenerateError> {
+        let mut numbers = Vec::new();
+        self.eval_many(&mut EvalContext::new(rng, Some(params)), &mut numbers)?;
+        Ok(numbers)
+    }
+
+    /*
+     * What this expression adds to the list of an aggregate, see `from_aggregate()`
+     */
+    fn list_bounds(&self) -> Vec<ListBounds> {
+        let one = |gex: &Gex| ListBounds { min_count: Decimal::ONE, max_count: Decimal::ONE, min_number: gex.min_number, max_number: gex.max_number };
         match &self.expression_type {
-            Expression::Number(out) => out.clone(),
-            Expression::Range(gex_x, gex_y, x_open, y_open) => self.eval_range(gex_x.generate(), gex_y.generate(), *x_open, *y_open),
-            Expression::Select(items) => Self::eval_select(items.iter().map(|gex| {
-                gex.generate()
-            }).collect()),
-            Expression::PrecalculatedRange(gex_x, gex_y, x_open, y_open, possible_vals) => {
-                Self::eval_precalculated(gex_x.generate(), gex_y.generate(), *x_open, *y_open, possible_vals)
-            }
+            Expression::Select(items, None) => items.iter().map(one).collect(),
+            Expression::Repeat(_, min_count, max_count) => vec![ListBounds {
+                min_count: min_count.min_number,
+                max_count: max_count.as_ref().unwrap_or(min_count).max_number,
+                min_number: self.min_number,
+                max_number: self.max_number,
+            }],
+            _ => vec![one(self)],
         }
     }
 
-    fn eval_range(&self, x: Decimal, y: Decimal, x_open: bool, y_open: bool) -> Decimal {
-        self.eval_range_hell(x, y, x_open, y_open, 0)
+    /*
+     * Size of the values this expression can generate, used by uniform selections.
+     * Ranges with a step (a constant "multiple of" constraint or a precision), precalculated ranges and dice can be counted,
+     * the rest of the expressions only have the length between their bounds.
+     */
+    fn support(&self) -> Support {
+        if self.length().is_zero() {
+            return Support::Count(Decimal::ONE)
+        }
+        match &self.expression_type {
+            Expression::PrecalculatedRange(_, _, _, _, values) => Support::Count(values.len().into()),
+            Expression::Dice(_) => Support::Count(self.length() + Decimal::ONE),
+            Expression::Range(gex_x, gex_y, x_open, y_open) => {
+                let step = self.mult_of.or(self.precision.map(|scale| Decimal::new(1, scale)));
+                match step {
+                    Some(step) => Support::Count(self.count_steps(step, gex_x, gex_y, *x_open, *y_open)),
+                    None => Support::Uncountable,
+                }
+            },
+            _ => Support::Uncountable,
+        }
     }
-    fn eval_range_hell(&self, x: Decimal, y: Decimal, x_open: bool, y_open: bool, iteration_n: usize) -> Decimal {
-        let mut number = if let Some(mult_of) = self.mult_of {
-            let x_range_mod = if x_open { mult_of } else { Decimal::ZERO };
-            let y_range_mod = if y_open { Decimal::ZERO } else { mult_of };
-            // println!("{}, {}", x + x_range_mod, y + y_range_mod);
-            random_decimal(x + x_range_mod, y + y_range_mod)
-        } else {
-            random_decimal(x, y)
+
+    /*
+     * Amount of multiples of step between the bounds. Open bounds are only excluded if they are constant,
+     * the rest of the bounds are the widest possible ones.
+     * Saturates if the amount doesn't fit in a Decimal.
+     */
+    fn count_steps(&self, step: Decimal, gex_x: &Gex, gex_y: &Gex, x_open: bool, y_open: bool) -> Decimal {
+        let (Some(first), Some(last)) = (self.min_number.checked_div(step), self.max_number.checked_div(step)) else {
+            return Decimal::MAX
         };
-        // This should basically never happen, but we have to check if we got exactly
-        // X or Y in supposedly open intervals.
-        // A bit strange how it's implemented but, again, it should never happen lol
-        if x_open && number == x {
-            number += dec!(0.001);
+        let (first, last) = (first.ceil(), last.floor());
+        let mut count = last.checked_sub(first).and_then(|count| count.checked_add(Decimal::ONE)).unwrap_or(Decimal::MAX);
+        let on_step = |gex: &Gex, multiple: Decimal| matches!(gex.expression_type, Expression::Number(bound) if multiple.checked_mul(step) == Some(bound));
+        if x_open && on_step(gex_x, first) {
+            count -= Decimal::ONE;
         }
-        else if y_open && number == y {
-            number -= dec!(0.001);
+        if y_open && on_step(gex_y, last) {
+            count -= Decimal::ONE;
         }
-        
-        for constraint in &self.dynamic_constraints {
-            match constraint {
-                Constraint::MultipleOf(mult_of) => {
-                    number = (number / mult_of).floor() * mult_of;
-                    // println!("{number} => {}", (number / mult_of).floor() * mult_of);
-                },
-                Constraint::NotMultipleOf(items) => {
-                    // Stop infinite rerolls when we tried a bunch of times with no... hehe.. dice
-                    if iteration_n > MAX_RANGE_HELL_REROLLS {
-                        break;
-                    }
+        count.max(Decimal::ZERO)
+    }
 
-                    // try again
-                    let mut is_mult: bool = false;
-                    for n in items {
-                        if number % n == Decimal::ZERO { is_mult = true; }
-                    }
-                    if is_mult {
-                        // Ohhh shit... here we go again
-                        number = self.eval_range_hell(x, y, x_open, y_open, iteration_n+1);
+    // Length between the bounds, saturates if it doesn't fit in a Decimal
+    fn length(&self) -> Decimal {
+        self.max_number.checked_sub(self.min_number).unwrap_or(Decimal::MAX)
+    }
+
+    fn eval(&self, context: &mut EvalContext) -> Result<Decimal, GenerateError> {
+        let number = match &self.expression_type {
+            Expression::Number(out) => Ok(*out),
+            Expression::Range(gex_x, gex_y, x_open, y_open) => {
+                let (x, y) = (gex_x.eval(context)?, gex_y.eval(context)?);
+                self.eval_range_hell(context.rng, x, y, *x_open, *y_open)
+            },
+            Expression::Select(items, alias_table) => {
+                // Only the chosen entry is generated
+                let index = Self::select_index(context.rng, items.len(), alias_table.as_ref());
+                items[index].eval(context)
+            },
+            Expression::PrecalculatedRange(gex_x, gex_y, x_open, y_open, possible_vals) => {
+                let (x, y) = (gex_x.eval(context)?, gex_y.eval(context)?);
+                Self::eval_precalculated(context.rng, x, y, *x_open, *y_open, possible_vals)
+            },
+            Expression::BinaryOp(operator, gex_x, gex_y) => {
+                let (x, y) = (gex_x.eval(context)?, gex_y.eval(context)?);
+                Self::eval_binary(*operator, x, y)
+            },
+            Expression::Bindings(definitions, body) => {
+                Self::eval_bindings(definitions, context)?;
+                body.eval(context)
+            },
+            Expression::Dice(dice) => Ok(dice.roll(context.rng)),
+            Expression::Aggregate(aggregate, args) => {
+                let mut values = Vec::new();
+                for arg in args {
+                    match &arg.expression_type {
+                        Expression::Select(items, None) => {
+                            for item in items {
+                                values.push(item.eval(context)?);
+                            }
+                        },
+                        _ => arg.eval_many(context, &mut values)?,
                     }
-                },
+                }
+                aggregate.apply(&mut values)
+            },
+            Expression::Call(function, args) => {
+                // Built-in functions take 3 arguments at most
+                let mut values = [Decimal::ZERO; 3];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(context)?;
+                }
+                function.apply(&values[..args.len()])
+            },
+            Expression::Combinator(gex, Combinator::Filter(predicate)) => self.reroll(|| gex.eval(context), |number| predicate(number)),
+            Expression::Combinator(gex, Combinator::Map(function)) => gex.eval(context).map(|number| function(number)),
+            Expression::Host(function, args) => {
+                let values = args.iter().map(|arg| arg.eval(context)).collect::<Result<Vec<Decimal>, GenerateError>>()?;
+                function.call(&values)
+            },
+            // Lists can only be generated by eval_many()
+            Expression::Repeat(..) => Err(GenerateError::MultipleValues),
+            Expression::BindingRef(index) => Ok(context.bindings[*index]),
+            Expression::Param(name) => context.params
+                .and_then(|params| params.get(name))
+                .ok_or_else(|| GenerateError::MissingParam(name.clone())),
+        }?;
+
+        match self.precision {
+            Some(scale) => {
+                let mut number = number.round_dp_with_strategy(scale, RoundingStrategy::MidpointNearestEven);
+                number.rescale(scale); // Adds the missing trailing zeros
+                Ok(number)
+            },
+            None => Ok(number),
+        }
+    }
+
+    /*
+     * Adds the numbers generated by the expression to the list: one per repetition if it has a quantifier, one otherwise.
+     */
+    fn eval_many(&self, context: &mut EvalContext, numbers: &mut Vec<Decimal>) -> Result<(), GenerateError> {
+        match &self.expression_type {
+            Expression::Repeat(gex, min_count, max_count) => {
+                let min_count = Self::eval_count(min_count.eval(context)?)?;
+                let count = match max_count {
+                    Some(max_count) => {
+                        let max_count = Self::eval_count(max_count.eval(context)?)?;
+                        if max_count < min_count {
+                            return Err(GenerateError::EmptyRange(min_count.into(), max_count.into()))
+                        }
+                        random_usize(context.rng, min_count, max_count + 1)
+                    },
+                    None => min_count,
+                };
+                numbers.reserve(count);
+                for _ in 0..count {
+                    numbers.push(gex.eval(context)?);
+                }
+                Ok(())
+            },
+            Expression::Bindings(definitions, body) => {
+                Self::eval_bindings(definitions, context)?;
+                body.eval_many(context, numbers)
+            },
+            _ => {
+                numbers.push(self.eval(context)?);
+                Ok(())
+            }
+        }
+    }
+
+    /*
+     * Every binding is generated once, expressions that use it get the same value.
+     * Bindings are only defined at the beginning of the program, so their indices start at 0. Values of previous
+     * attempts (when a filter rerolls the program) are discarded so that the indices point to the new values.
+     */
+    fn eval_bindings(definitions: &[Gex], context: &mut EvalContext) -> Result<(), GenerateError> {
+        context.bindings.clear();
+        for definition in definitions {
+            let value = definition.eval(context)?;
+            context.bindings.push(value);
+        }
+        Ok(())
+    }
+
+    // Counts are generated as integers, but parameters can be outside of their declared bounds
+    fn eval_count(count: Decimal) -> Result<usize, GenerateError> {
+        count.to_usize()
+            .filter(|repetitions| count.fract().is_zero() && *repetitions <= MAX_REPETITIONS)
+            .ok_or(GenerateError::InvalidCount(count))
+    }
+
+    /*
+     * Generates numbers until one of them satisfies the NotMultipleOf constraints.
+     * Gives up after the maximum amount of rerolls.
+     */
+    fn eval_range_hell(&self, rng: &mut dyn GrandRng, x: Decimal, y: Decimal, x_open: bool, y_open: bool) -> Result<Decimal, GenerateError> {
+        self.reroll(|| self.eval_range(rng, x, y, x_open, y_open), |number| {
+            !self.dynamic_constraints.iter().any(|constraint| match constraint {
+                Constraint::NotMultipleOf(items) => items.iter().any(|n| number % n == Decimal::ZERO),
+                Constraint::MultipleOf(_) => false,
+            })
+        })
+    }
+    /*
+     * Generates numbers until one of them is accepted, used by constraints and filters.
+     * Gives up after the maximum amount of rerolls.
+     */
+    fn reroll(&self, mut generate: impl FnMut() -> Result<Decimal, GenerateError>, accept: impl Fn(&Decimal) -> bool) -> Result<Decimal, GenerateError> {
+        // Stop infinite rerolls when we tried a bunch of times with no... hehe.. dice
+        for _ in 0..=self.max_rerolls {
+            let number = generate()?;
+            if accept(&number) {
+                return Ok(number)
+            }
+            // Ohhh shit... here we go again
+        }
+
+        Err(GenerateError::RerollsExhausted(self.max_rerolls))
+    }
+    fn eval_range(&self, rng: &mut dyn GrandRng, x: Decimal, y: Decimal, x_open: bool, y_open: bool) -> Result<Decimal, GenerateError> {
+        if y < x {
+            return Err(GenerateError::EmptyRange(x, y))
+        }
+        let conversion_error = || GenerateError::NumericConversion(x, y);
+
+        // Multiples (and integers) are generated directly: we get a random integer n and return n * mult_of
+        let step = match (self.mult_of, self.precision) {
+            (Some(mult_of), _) => Some(mult_of),
+            (None, Some(0)) => Some(Decimal::ONE),
+            (None, _) => None,
+        };
+        if let Some(mult_of) = step {
+            let mut min = x.checked_div(mult_of).ok_or_else(conversion_error)?.ceil();
+            let mut max = y.checked_div(mult_of).ok_or_else(conversion_error)?.floor();
+            if x_open && min * mult_of == x {
+                min += Decimal::ONE;
+            }
+            if y_open && max * mult_of == y {
+                max -= Decimal::ONE;
+            }
+            if max < min {
+                return Err(GenerateError::EmptyRange(x, y))
             }
+            let multiple = random_decimal_int(rng, min, max)?;
+            return multiple.checked_mul(mult_of).ok_or_else(conversion_error)
         }
 
-        number
+        random_decimal(rng, x, y, x_open, y_open, self.precision)
     }
 
-    fn eval_select(options: Vec<Decimal>) -> Decimal {
-        let random_index = random_usize(0, options.len());
-        return options.get(random_index).expect("Out of range. Random index generated was incorrect").clone()
+    fn eval_binary(operator: BinaryOperator, x: Decimal, y: Decimal) -> Result<Decimal, GenerateError> {
+        if y.is_zero() && matches!(operator, BinaryOperator::Divide | BinaryOperator::Remainder) {
+            return Err(GenerateError::DivisionByZero(x))
+        }
+        operator.apply(x, y).ok_or(GenerateError::NumericConversion(x, y))
+    }
+
+    /*
+     * Chooses the entry of a selection before generating anything, so the other entries
+     * don't use randomness (or rerolls) and no list of results has to be allocated.
+     */
+    fn select_index(rng: &mut dyn GrandRng, len: usize, alias_table: Option<&AliasTable>) -> usize {
+        match alias_table {
+            Some(alias_table) => alias_table.sample(rng),
+            None => random_usize(rng, 0, len),
+        }
     }
 
     /*
      * Used in constraints whenever the range is small enough to be inside
-     * the memory budget (configurable, probably 1MB-ish by default).
+     * the memory budget (configurable, 128KB by default).
      * 
      * The possible_vals array is assumed to be sorted.
      */
-    fn eval_precalculated(x: Decimal, y: Decimal, x_open: bool, y_open: bool, possible_vals: &Vec<Decimal>) -> Decimal {
+    fn eval_precalculated(rng: &mut dyn GrandRng, x: Decimal, y: Decimal, x_open: bool, y_open: bool, possible_vals: &[Decimal]) -> Result<Decimal, GenerateError> {
+        // Dynamic bounds may not contain any of the precalculated values
         let min_index = possible_vals.iter().position(|num| {
             if x_open {
                 *num > x
             } else {
                 *num >= x
             }
-        }).expect("Minimum possible value within range not found. Should be impossible?");
+        }).ok_or(GenerateError::EmptyRange(x, y))?;
 
-        let mut max_index = possible_vals.iter().position(|num| {
+        let max_index = possible_vals.iter().position(|num| {
             // We return the position of the first INvalid value.
             // The last valid value is the one in the previous index.
-            *num >= y
+            if y_open {
+                *num >= y
+            } else {
+                *num > y
+            }
         }).unwrap_or(possible_vals.len());
-        // If our current max index is the same as the maximum value expected and this is
-        // an open interval we can't take this number, we should take the previous one.
-        if y_open && *possible_vals.get(max_index-1).unwrap() == y {
-            max_index -= 1;
+        if max_index <= min_index {
+            return Err(GenerateError::EmptyRange(x, y))
         }
 
         // Now we get an index within the range and return the precalculated value at that position
-        let index = random_usize(min_index, max_index); // from min_index (inclusive) to max_index (without reaching it), this won't overflow
-        *possible_vals.get(index).unwrap()
+        let index = random_usize(rng, min_index, max_index); // from min_index (inclusive) to max_index (without reaching it), this won't overflow
+        Ok(possible_vals[index])
     }
-}
\ No newline at end of file
+}
+
+// Size of the support of an expression, see `Gex::support()`
+enum Support {
+    Count(Decimal),
+    Uncountable, // Only the length between the bounds is known
+}
@@ -0,0 +1,107 @@
+use rust_decimal::Decimal;
+
+use super::generate_error::GenerateError;
+
+/*
+ * Functions that combine a list of numbers into one (`sum((1..6){3})`, `max([0..10, 5..20])`).
+ * Kth is the k-th smallest number, starting from 1.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Kth(usize),
+}
+
+/*
+ * What we know about a part of the list of an aggregate while compiling:
+ * the amount of numbers it adds to the list and the bounds of every one of them.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ListBounds {
+    pub min_count: Decimal,
+    pub max_count: Decimal,
+    pub min_number: Decimal,
+    pub max_number: Decimal,
+}
+
+impl Aggregate {
+    /*
+     * Names used in expressions. Kth isn't complete until we read its first argument
+     */
+    pub fn from_name(name: &str) -> Option<Aggregate> {
+        match name {
+            "sum" => Some(Aggregate::Sum),
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            "avg" => Some(Aggregate::Avg),
+            "kth" => Some(Aggregate::Kth(1)),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, values: &mut [Decimal]) -> Result<Decimal, GenerateError> {
+        let needed = match self {
+            Aggregate::Sum => 0,
+            Aggregate::Kth(k) => *k,
+            _ => 1,
+        };
+        if values.len() < needed {
+            return Err(GenerateError::NotEnoughValues(needed, values.len()))
+        }
+        match self {
+            Aggregate::Sum => Self::sum(values),
+            Aggregate::Min => Ok(values.iter().copied().fold(Decimal::MAX, Decimal::min)),
+            Aggregate::Max => Ok(values.iter().copied().fold(Decimal::MIN, Decimal::max)),
+            Aggregate::Avg => {
+                let sum = Self::sum(values)?;
+                sum.checked_div(values.len().into()).ok_or(GenerateError::NumericConversion(sum, values.len().into()))
+            },
+            Aggregate::Kth(k) => {
+                values.sort_unstable();
+                Ok(values[k - 1])
+            },
+        }
+    }
+
+    fn sum(values: &[Decimal]) -> Result<Decimal, GenerateError> {
+        values.iter().try_fold(Decimal::ZERO, |sum, value| {
+            sum.checked_add(*value).ok_or(GenerateError::NumericConversion(sum, *value))
+        })
+    }
+
+    /*
+     * Smallest and biggest possible results for lists made of the given parts.
+     * Results that can't be represented are clamped to the limits of a Decimal.
+     */
+    pub fn bounds(&self, parts: &[ListBounds]) -> (Decimal, Decimal) {
+        // Bounds of any number of the list
+        let lowest = parts.iter().map(|part| part.min_number).fold(Decimal::MAX, Decimal::min);
+        let highest = parts.iter().map(|part| part.max_number).fold(Decimal::MIN, Decimal::max);
+        // Parts that always add a number to the list
+        let always = parts.iter().filter(|part| part.min_count >= Decimal::ONE);
+
+        match self {
+            Aggregate::Sum => parts.iter().fold((Decimal::ZERO, Decimal::ZERO), |(min, max), part| {
+                // Negative numbers make the sum smaller the more there are, positive numbers make it bigger
+                let smallest = Decimal::min(saturating_mul(part.min_count, part.min_number), saturating_mul(part.max_count, part.min_number));
+                let biggest = Decimal::max(saturating_mul(part.min_count, part.max_number), saturating_mul(part.max_count, part.max_number));
+                (saturating_add(min, smallest), saturating_add(max, biggest))
+            }),
+            // The maximum is at least as big as any number that is always generated, and the minimum at least as small
+            Aggregate::Max => (always.map(|part| part.min_number).reduce(Decimal::max).unwrap_or(lowest), highest),
+            Aggregate::Min => (lowest, always.map(|part| part.max_number).reduce(Decimal::min).unwrap_or(highest)),
+            Aggregate::Avg | Aggregate::Kth(_) => (lowest, highest),
+        }
+    }
+}
+
+fn saturating_mul(x: Decimal, y: Decimal) -> Decimal {
+    x.checked_mul(y).unwrap_or(if x.is_sign_negative() != y.is_sign_negative() { Decimal::MIN } else { Decimal::MAX })
+}
+
+fn saturating_add(x: Decimal, y: Decimal) -> Decimal {
+    x.checked_add(y).unwrap_or(if x.is_sign_negative() { Decimal::MIN } else { Decimal::MAX })
+}
@@ -0,0 +1,80 @@
+use rust_decimal::Decimal;
+
+use crate::rng_functions::{random_below_u128, random_usize};
+use crate::rng_traits::GrandRng;
+
+/*
+ * Alias table (Vose's method) to pick indices with different weights in constant time.
+ *
+ * Every index has a column with a capacity of `total`. The column of an index is filled with
+ * its own weight (scaled) and the rest is filled by another index (the alias).
+ * To pick an index we choose a column uniformly and a random number below `total`, if the number
+ * is below the weight stored in the column we get the index of the column, if not we get its alias.
+ *
+ * Weights are converted into integers so that the probabilities are exact.
+ */
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+    thresholds: Vec<u128>,
+    aliases: Vec<usize>,
+    total: u128,
+}
+
+impl AliasTable {
+    /*
+     * Weights must be non-negative and their total can't be zero.
+     * Returns None if the weights are too big (or have too many decimals) to be converted into integers.
+     */
+    pub fn new(weights: &[Decimal]) -> Option<AliasTable> {
+        // Every weight is converted to an integer with the same scale
+        let weights: Vec<Decimal> = weights.iter().map(|weight| weight.normalize()).collect();
+        let scale = weights.iter().map(|weight| weight.scale()).max()?;
+        let integer_weights = weights.iter().map(|weight| {
+            let mantissa = u128::try_from(weight.mantissa()).ok()?;
+            mantissa.checked_mul(10u128.checked_pow(scale - weight.scale())?)
+        }).collect::<Option<Vec<u128>>>()?;
+
+        AliasTable::from_integers(&integer_weights)
+    }
+
+    /*
+     * Same as new() but with integer weights (like the amount of times each result can happen).
+     * Returns None if the total is zero or too big.
+     */
+    pub fn from_integers(integer_weights: &[u128]) -> Option<AliasTable> {
+        let count = integer_weights.len() as u128;
+        let total = integer_weights.iter().try_fold(0u128, |total, weight| total.checked_add(*weight))?;
+        if total == 0 {
+            return None
+        }
+        // Weights scaled so that the average weight is the capacity of a column (total)
+        let mut scaled = integer_weights.iter().map(|weight| weight.checked_mul(count)).collect::<Option<Vec<u128>>>()?;
+
+        let mut thresholds = vec![total; scaled.len()];
+        let mut aliases: Vec<usize> = (0..scaled.len()).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..scaled.len()).partition(|index| scaled[*index] < total);
+        while let (Some(small_index), Some(large_index)) = (small.pop(), large.pop()) {
+            // The rest of the small column is filled by the large one
+            thresholds[small_index] = scaled[small_index];
+            aliases[small_index] = large_index;
+            scaled[large_index] -= total - scaled[small_index];
+            if scaled[large_index] < total {
+                small.push(large_index);
+            } else {
+                large.push(large_index);
+            }
+        }
+        // Columns left are full (their threshold is already the total)
+
+        Some(AliasTable { thresholds, aliases, total })
+    }
+
+    pub fn sample(&self, rng: &mut dyn GrandRng) -> usize {
+        let column = random_usize(rng, 0, self.thresholds.len());
+        if random_below_u128(rng, self.total) < self.thresholds[column] {
+            column
+        } else {
+            self.aliases[column]
+        }
+    }
+}
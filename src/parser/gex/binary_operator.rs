@@ -0,0 +1,65 @@
+use rust_decimal::Decimal;
+
+/*
+ * Arithmetic between the results of two expressions.
+ * Operations that can't be represented (overflow, division by zero) return None.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOperator {
+    pub fn apply(&self, x: Decimal, y: Decimal) -> Option<Decimal> {
+        match self {
+            BinaryOperator::Add => x.checked_add(y),
+            BinaryOperator::Subtract => x.checked_sub(y),
+            BinaryOperator::Multiply => x.checked_mul(y),
+            BinaryOperator::Divide => x.checked_div(y),
+            BinaryOperator::Remainder => x.checked_rem(y),
+        }
+    }
+
+    /*
+     * Smallest and biggest possible results for operands within the given bounds.
+     * Results that can't be represented are clamped to the limits of a Decimal.
+     */
+    pub fn bounds(&self, (x_min, x_max): (Decimal, Decimal), (y_min, y_max): (Decimal, Decimal)) -> (Decimal, Decimal) {
+        let saturate = |result: Option<Decimal>, positive: bool| result.unwrap_or(if positive { Decimal::MAX } else { Decimal::MIN });
+        match self {
+            BinaryOperator::Add => (
+                saturate(x_min.checked_add(y_min), x_min.is_sign_positive()),
+                saturate(x_max.checked_add(y_max), x_max.is_sign_positive()),
+            ),
+            BinaryOperator::Subtract => (
+                saturate(x_min.checked_sub(y_max), x_min.is_sign_positive()),
+                saturate(x_max.checked_sub(y_min), x_max.is_sign_positive()),
+            ),
+            // The extremes are always in the corners
+            BinaryOperator::Multiply | BinaryOperator::Divide => {
+                // Dividing by numbers close to 0 can give any number
+                if *self == BinaryOperator::Divide && y_min <= Decimal::ZERO && y_max >= Decimal::ZERO {
+                    return (Decimal::MIN, Decimal::MAX)
+                }
+                let corners = [(x_min, y_min), (x_min, y_max), (x_max, y_min), (x_max, y_max)]
+                    .map(|(x, y)| saturate(self.apply(x, y), x.is_sign_positive() == y.is_sign_positive()));
+                (
+                    corners.iter().copied().fold(Decimal::MAX, Decimal::min),
+                    corners.iter().copied().fold(Decimal::MIN, Decimal::max),
+                )
+            },
+            // The remainder has the sign of X and it's smaller than the biggest Y
+            BinaryOperator::Remainder => {
+                let y_abs_max = y_min.abs().max(y_max.abs());
+                (
+                    x_min.max(-y_abs_max).min(Decimal::ZERO),
+                    x_max.min(y_abs_max).max(Decimal::ZERO),
+                )
+            },
+        }
+    }
+}
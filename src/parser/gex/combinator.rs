@@ -0,0 +1,23 @@
+use std::{fmt::Debug, sync::Arc};
+
+use rust_decimal::Decimal;
+
+/*
+ * Closures added to a compiled expression from Rust (`Gex::filter()`, `Gex::map()`).
+ * Filters reroll the expression until the number satisfies the predicate, maps transform the number generated.
+ * They are thread-safe so compiled expressions can be shared between threads.
+ */
+#[derive(Clone)]
+pub enum Combinator {
+    Filter(Arc<dyn Fn(&Decimal) -> bool + Send + Sync>),
+    Map(Arc<dyn Fn(Decimal) -> Decimal + Send + Sync>),
+}
+
+impl Debug for Combinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combinator::Filter(_) => write!(f, "Filter"),
+            Combinator::Map(_) => write!(f, "Map"),
+        }
+    }
+}
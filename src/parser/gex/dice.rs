@@ -0,0 +1,168 @@
+use rust_decimal::Decimal;
+
+use crate::rng_functions::random_usize;
+use crate::rng_traits::GrandRng;
+
+use super::alias_table::AliasTable;
+
+/// Maximum amount of dice in a roll and of sides of a die (`100000d100000`). Keeps the time it takes to roll bounded
+pub const MAX_DICE: u32 = 100_000;
+
+/// Maximum amount of times a single die can explode. Makes the bounds of exploding dice finite
+pub const MAX_EXPLOSIONS: u32 = 100;
+
+/*
+ * Maximum amount of work done while compiling to calculate the distribution of a roll.
+ * Sums are convolutions (count² × sides²) and kept dice are enumerated (sides^count × count).
+ */
+const MAX_PRECALC_WORK: u128 = 1 << 20;
+
+/// Dice that are kept after rolling (`4d6kh3` keeps the 3 highest). Dropping dice (`4d6dl1`)
+/// is the same as keeping the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    Highest(u32),
+    Lowest(u32),
+}
+
+/*
+ * A roll of `count` dice with `sides` sides (`3d6`). The result is the sum of the kept dice.
+ * Exploding dice are rolled again (and added) every time they get the highest value.
+ *
+ * Rolls of small pools are precalculated: every possible result and the amount of ways to get it
+ * are stored in an alias table, so generating a result takes the same time no matter how many dice there are.
+ */
+#[derive(Debug, Clone)]
+pub struct Dice {
+    count: u32,
+    sides: u32,
+    exploding: bool,
+    keep: Option<Keep>,
+    distribution: Option<(Decimal, AliasTable)>, // Smallest result and alias table of every result from there
+}
+
+impl Dice {
+    /*
+     * Amounts of dice kept must be between 1 and `count`, and dice with 1 side can't explode (they would always explode).
+     * This is checked by the parser.
+     */
+    pub fn new(count: u32, sides: u32, exploding: bool, keep: Option<Keep>) -> Self {
+        let mut dice = Dice { count, sides, exploding, keep, distribution: None };
+        if !exploding {
+            dice.distribution = dice.precalculate();
+        }
+        dice
+    }
+
+    pub fn kept(&self) -> u32 {
+        match self.keep {
+            Some(Keep::Highest(kept)) | Some(Keep::Lowest(kept)) => kept,
+            None => self.count,
+        }
+    }
+
+    /// Smallest and biggest possible results
+    pub fn bounds(&self) -> (Decimal, Decimal) {
+        let kept = Decimal::from(self.kept());
+        let explosions = if self.exploding { MAX_EXPLOSIONS + 1 } else { 1 };
+        let max_die = Decimal::from(self.sides) * Decimal::from(explosions);
+        (kept, kept * max_die)
+    }
+
+    pub fn roll(&self, rng: &mut dyn GrandRng) -> Decimal {
+        if let Some((min, alias_table)) = &self.distribution {
+            return min + Decimal::from(alias_table.sample(rng))
+        }
+        let total: u128 = match self.keep {
+            None => (0..self.count).map(|_| self.roll_die(rng)).sum(),
+            Some(keep) => {
+                let mut rolls: Vec<u128> = (0..self.count).map(|_| self.roll_die(rng)).collect();
+                rolls.sort_unstable();
+                match keep {
+                    Keep::Highest(kept) => rolls.iter().rev().take(kept as usize).sum(),
+                    Keep::Lowest(kept) => rolls.iter().take(kept as usize).sum(),
+                }
+            }
+        };
+        Decimal::from(total)
+    }
+
+    fn roll_die(&self, rng: &mut dyn GrandRng) -> u128 {
+        let sides = self.sides as usize;
+        let mut total = 0;
+        for _ in 0..=MAX_EXPLOSIONS {
+            let face = random_usize(rng, 0, sides) + 1;
+            total += face as u128;
+            if !self.exploding || face != sides {
+                break;
+            }
+        }
+        total
+    }
+
+    /*
+     * Amount of ways to get every result (from the smallest one), as long as
+     * calculating them is cheap enough and they fit in the alias table.
+     */
+    fn precalculate(&self) -> Option<(Decimal, AliasTable)> {
+        let (count, sides) = (self.count as u128, self.sides as u128);
+        let ways = match self.keep {
+            None => {
+                let work = count.checked_mul(count)?.checked_mul(sides)?.checked_mul(sides)?;
+                if work > MAX_PRECALC_WORK {
+                    return None
+                }
+                self.convolve()?
+            },
+            Some(keep) => {
+                let work = sides.checked_pow(self.count)?.checked_mul(count)?;
+                if work > MAX_PRECALC_WORK {
+                    return None
+                }
+                self.enumerate(keep)
+            }
+        };
+        let alias_table = AliasTable::from_integers(&ways)?;
+        Some((Decimal::from(self.kept()), alias_table))
+    }
+
+    // Ways to get every sum of all the dice (from `count`), adding one die at a time
+    fn convolve(&self) -> Option<Vec<u128>> {
+        let sides = self.sides as usize;
+        let mut ways: Vec<u128> = vec![1];
+        for _ in 0..self.count {
+            let mut next = vec![0u128; ways.len() + sides - 1];
+            for (sum, sum_ways) in ways.iter().enumerate() {
+                for face in 0..sides {
+                    next[sum + face] = next[sum + face].checked_add(*sum_ways)?;
+                }
+            }
+            ways = next;
+        }
+        Some(ways)
+    }
+
+    // Ways to get every sum of the kept dice (from `kept`), checking every possible roll
+    fn enumerate(&self, keep: Keep) -> Vec<u128> {
+        let (count, sides, kept) = (self.count as usize, self.sides, self.kept() as usize);
+        let mut ways = vec![0u128; kept * (sides as usize - 1) + 1];
+        let mut faces = vec![1u32; count];
+        let mut sorted = vec![0u32; count];
+        loop {
+            sorted.copy_from_slice(&faces);
+            sorted.sort_unstable();
+            let sum: usize = match keep {
+                Keep::Highest(_) => sorted.iter().rev().take(kept).map(|face| *face as usize).sum(),
+                Keep::Lowest(_) => sorted.iter().take(kept).map(|face| *face as usize).sum(),
+            };
+            ways[sum - kept] += 1;
+
+            // Next roll, like an odometer
+            let Some(position) = faces.iter().position(|face| *face < sides) else {
+                return ways
+            };
+            faces[position] += 1;
+            faces[..position].fill(1);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use rust_decimal::Decimal;
+
+use crate::rng_traits::GrandRng;
+
+use super::params::Params;
+
+/*
+ * State of a single generation. It's created every time a number is generated
+ * and passed to every expression in the tree.
+ */
+pub(crate) struct EvalContext<'a> {
+    pub rng: &'a mut dyn GrandRng,
+    pub bindings: Vec<Decimal>, // Values of the bindings, in the order they were defined
+    pub params: Option<&'a Params>,
+}
+
+impl<'a> EvalContext<'a> {
+    pub fn new(rng: &'a mut dyn GrandRng, params: Option<&'a Params>) -> Self {
+        EvalContext { rng, bindings: Vec::new(), params }
+    }
+}
@@ -1,11 +1,21 @@
 use rust_decimal::Decimal;
 
-use super::Gex;
+use super::{aggregate::Aggregate, alias_table::AliasTable, binary_operator::BinaryOperator, combinator::Combinator, dice::Dice, function::Function, registry::HostFunction, Gex};
 
 #[derive(Debug, Clone)]
 pub enum Expression {
     Number(Decimal),
     Range(Box<Gex>, Box<Gex>, bool, bool), // X, Y, X is Open, Y is Open
-    Select(Vec<Box<Gex>>),
+    Select(Vec<Gex>, Option<AliasTable>), // Entries, alias table if the entries have weights
     PrecalculatedRange(Box<Gex>, Box<Gex>, bool, bool, Vec<Decimal>), // X, Y, X is Open, Y is Open, possible values
+    BinaryOp(BinaryOperator, Box<Gex>, Box<Gex>), // Operator, X, Y
+    Dice(Dice),
+    Repeat(Box<Gex>, Box<Gex>, Option<Box<Gex>>), // Repeated expression, count (or minimum count), maximum count
+    Aggregate(Aggregate, Vec<Gex>), // Function, arguments
+    Call(Function, Vec<Gex>), // Built-in function, arguments
+    Host(HostFunction, Vec<Gex>), // Registered function, arguments
+    Combinator(Box<Gex>, Combinator), // Wrapped expression, closure added from Rust
+    Bindings(Vec<Gex>, Box<Gex>), // Definitions (in order), body
+    BindingRef(usize), // Index of the definition
+    Param(String), // Name of the parameter
 }
\ No newline at end of file
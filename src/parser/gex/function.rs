@@ -0,0 +1,225 @@
+use rust_decimal::{prelude::ToPrimitive, Decimal, MathematicalOps, RoundingStrategy};
+
+use super::generate_error::GenerateError;
+
+/*
+ * Built-in numeric functions (`abs(-5..5)`, `round(0..10, 2)`, `pow(2, 0..10)`).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Clamp,
+    Pow,
+    Log,
+}
+
+/*
+ * Every built-in function with its name and its minimum and maximum amount of arguments
+ */
+const FUNCTIONS: [(&str, Function, usize, usize); 7] = [
+    ("abs", Function::Abs, 1, 1),
+    ("floor", Function::Floor, 1, 1),
+    ("ceil", Function::Ceil, 1, 1),
+    ("round", Function::Round, 1, 2),   // Number, decimals (0 by default)
+    ("clamp", Function::Clamp, 3, 3),   // Number, minimum, maximum
+    ("pow", Function::Pow, 2, 2),       // Base, exponent
+    ("log", Function::Log, 1, 2),       // Number, base (natural logarithm by default)
+];
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Function> {
+        FUNCTIONS.iter().find(|(function_name, _, _, _)| *function_name == name).map(|(_, function, _, _)| *function)
+    }
+
+    pub fn name(&self) -> &'static str {
+        FUNCTIONS.iter().find(|(_, function, _, _)| function == self).map(|(name, _, _, _)| *name).unwrap_or_default()
+    }
+
+    /// Whether the function can be called with this amount of arguments
+    pub fn accepts(&self, count: usize) -> bool {
+        FUNCTIONS.iter().any(|(_, function, min, max)| function == self && (*min..=*max).contains(&count))
+    }
+
+    fn min_args(&self) -> usize {
+        FUNCTIONS.iter().find(|(_, function, _, _)| function == self).map(|(_, _, min, _)| *min).unwrap_or_default()
+    }
+
+    /*
+     * Index of an argument that can never be valid, given the bounds of every argument.
+     * Arguments that are only invalid sometimes are reported while generating (GenerateError::InvalidArgument).
+     */
+    pub fn invalid_argument(&self, args: &[(Decimal, Decimal)]) -> Option<usize> {
+        match (self, args) {
+            (Function::Round, [_, (min_decimals, max_decimals)]) => {
+                (*max_decimals < Decimal::ZERO || *min_decimals > Decimal::from(Decimal::MAX_SCALE)).then_some(1)
+            },
+            (Function::Clamp, [_, (min, _), (_, max)]) => (min > max).then_some(1),
+            (Function::Log, [(_, max), ..]) if *max <= Decimal::ZERO => Some(0),
+            (Function::Log, [_, (min_base, max_base)]) => {
+                (*max_base <= Decimal::ZERO || (*min_base == Decimal::ONE && *max_base == Decimal::ONE)).then_some(1)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, args: &[Decimal]) -> Result<Decimal, GenerateError> {
+        let invalid = |value: Decimal| GenerateError::InvalidArgument(self.name().to_owned(), value);
+        match (self, args) {
+            (Function::Abs, [x, ..]) => Ok(x.abs()),
+            (Function::Floor, [x, ..]) => Ok(x.floor()),
+            (Function::Ceil, [x, ..]) => Ok(x.ceil()),
+            (Function::Round, [x]) => Ok(x.round_dp_with_strategy(0, RoundingStrategy::MidpointNearestEven)),
+            (Function::Round, [x, decimals, ..]) => {
+                let scale = decimals.to_u32()
+                    .filter(|scale| decimals.fract().is_zero() && *scale <= Decimal::MAX_SCALE)
+                    .ok_or_else(|| invalid(*decimals))?;
+                Ok(x.round_dp_with_strategy(scale, RoundingStrategy::MidpointNearestEven))
+            },
+            (Function::Clamp, [x, min, max, ..]) => {
+                if min > max {
+                    return Err(invalid(*min))
+                }
+                Ok((*x).max(*min).min(*max))
+            },
+            (Function::Pow, [base, exponent, ..]) => {
+                if exponent.fract().is_zero() {
+                    let exponent_int = exponent.to_i64().ok_or(GenerateError::NumericConversion(*base, *exponent))?;
+                    return base.checked_powi(exponent_int).ok_or(GenerateError::NumericConversion(*base, *exponent))
+                }
+                // Roots of negative numbers
+                if base.is_sign_negative() && !base.is_zero() {
+                    return Err(invalid(*base))
+                }
+                base.checked_powd(*exponent).ok_or(GenerateError::NumericConversion(*base, *exponent))
+            },
+            (Function::Log, [x, rest @ ..]) => {
+                if *x <= Decimal::ZERO {
+                    return Err(invalid(*x))
+                }
+                let ln = x.checked_ln().ok_or_else(|| invalid(*x))?;
+                match rest {
+                    [base, ..] if *base <= Decimal::ZERO || *base == Decimal::ONE => Err(invalid(*base)),
+                    [base, ..] => {
+                        let ln_base = base.checked_ln().ok_or_else(|| invalid(*base))?;
+                        ln.checked_div(ln_base).ok_or(GenerateError::NumericConversion(*x, *base))
+                    },
+                    _ => Ok(ln),
+                }
+            },
+            // The parser checks the amount of arguments, extra arguments are ignored
+            _ => Err(GenerateError::NotEnoughValues(self.min_args(), args.len())),
+        }
+    }
+
+    /*
+     * Smallest and biggest possible results for arguments within the given bounds.
+     * Results that can't be represented are clamped to the limits of a Decimal.
+     */
+    pub fn bounds(&self, args: &[(Decimal, Decimal)]) -> (Decimal, Decimal) {
+        const FULL: (Decimal, Decimal) = (Decimal::MIN, Decimal::MAX);
+        match (self, args) {
+            (Function::Abs, [(min, max)]) => {
+                if *min >= Decimal::ZERO {
+                    (*min, *max)
+                } else if *max <= Decimal::ZERO {
+                    (-*max, -*min)
+                } else {
+                    (Decimal::ZERO, Decimal::max(-*min, *max))
+                }
+            },
+            (Function::Floor, [(min, max)]) => (min.floor(), max.floor()),
+            (Function::Ceil, [(min, max)]) => (min.ceil(), max.ceil()),
+            (Function::Round, [(min, max), rest @ ..]) => {
+                // Rounding with more decimals never goes beyond rounding with the fewest decimals possible
+                let scale = match rest {
+                    [(min_decimals, _)] => (*min_decimals).max(Decimal::ZERO).min(Decimal::from(Decimal::MAX_SCALE)).floor().to_u32().unwrap_or(0),
+                    _ => 0,
+                };
+                (min.round_dp_with_strategy(scale, RoundingStrategy::ToNegativeInfinity), max.round_dp_with_strategy(scale, RoundingStrategy::ToPositiveInfinity))
+            },
+            (Function::Clamp, [(x_min, x_max), (min_min, min_max), (max_min, max_max)]) => {
+                ((*x_min).max(*min_min).min(*max_min), (*x_max).max(*min_max).min(*max_max))
+            },
+            (Function::Pow, [(base_min, base_max), (exponent_min, exponent_max)]) => {
+                let constant_int = (exponent_min == exponent_max && exponent_min.fract().is_zero()).then(|| exponent_min.to_i64()).flatten();
+                let spans_zero = *base_min <= Decimal::ZERO && *base_max >= Decimal::ZERO;
+                match constant_int {
+                    // Negative powers of numbers close to 0 are as big as they can be
+                    Some(exponent) if exponent < 0 && spans_zero => FULL,
+                    // The result only changes direction at 0, so it's enough to check the bounds (and 0)
+                    Some(exponent) => {
+                        let mut candidates = vec![saturating_powi(*base_min, exponent), saturating_powi(*base_max, exponent)];
+                        if spans_zero {
+                            candidates.push(saturating_powi(Decimal::ZERO, exponent));
+                        }
+                        min_max(&candidates)
+                    },
+                    // Negative numbers can't have decimal exponents
+                    None if base_min.is_sign_negative() && !base_min.is_zero() => FULL,
+                    // exponent × ln(base) is the biggest and smallest at the corners
+                    None => min_max(&[
+                        saturating_powd(*base_min, *exponent_min),
+                        saturating_powd(*base_min, *exponent_max),
+                        saturating_powd(*base_max, *exponent_min),
+                        saturating_powd(*base_max, *exponent_max),
+                    ]),
+                }
+            },
+            (Function::Log, [(x_min, x_max), rest @ ..]) => {
+                // Numbers that can be 0 or negative make the logarithm as small as it can be
+                let ln_min = if *x_min > Decimal::ZERO { x_min.checked_ln() } else { None };
+                let ln_x = (ln_min.unwrap_or(Decimal::MIN), x_max.checked_ln().unwrap_or(Decimal::MAX));
+                match rest {
+                    [] => ln_x,
+                    // Bases around 1 make the result as big as it can be
+                    [(base_min, base_max)] if *base_min <= Decimal::ONE && *base_max >= Decimal::ONE => FULL,
+                    [(base_min, _)] if *base_min <= Decimal::ZERO => FULL,
+                    [_] if ln_min.is_none() => FULL,
+                    [(base_min, base_max)] => {
+                        let (Some(ln_base_min), Some(ln_base_max)) = (base_min.checked_ln(), base_max.checked_ln()) else {
+                            return FULL
+                        };
+                        min_max(&[
+                            saturating_div(ln_x.0, ln_base_min),
+                            saturating_div(ln_x.0, ln_base_max),
+                            saturating_div(ln_x.1, ln_base_min),
+                            saturating_div(ln_x.1, ln_base_max),
+                        ])
+                    },
+                    _ => FULL,
+                }
+            },
+            _ => FULL,
+        }
+    }
+}
+
+fn min_max(candidates: &[Decimal]) -> (Decimal, Decimal) {
+    let min = candidates.iter().copied().fold(Decimal::MAX, Decimal::min);
+    let max = candidates.iter().copied().fold(Decimal::MIN, Decimal::max);
+    (min, max)
+}
+
+// Powers that can't be represented are as big as possible (or as small, for odd powers of negative numbers)
+fn saturating_powi(base: Decimal, exponent: i64) -> Decimal {
+    base.checked_powi(exponent).unwrap_or(if base.is_sign_negative() && exponent % 2 != 0 { Decimal::MIN } else { Decimal::MAX })
+}
+
+// Only for positive bases (or 0)
+fn saturating_powd(base: Decimal, exponent: Decimal) -> Decimal {
+    if base.is_zero() {
+        return match exponent.cmp(&Decimal::ZERO) {
+            std::cmp::Ordering::Greater => Decimal::ZERO,
+            std::cmp::Ordering::Equal => Decimal::ONE,
+            std::cmp::Ordering::Less => Decimal::MAX,
+        }
+    }
+    base.checked_powd(exponent).unwrap_or(Decimal::MAX)
+}
+
+fn saturating_div(x: Decimal, y: Decimal) -> Decimal {
+    x.checked_div(y).unwrap_or(if x.is_sign_negative() != y.is_sign_negative() { Decimal::MIN } else { Decimal::MAX })
+}
@@ -0,0 +1,131 @@
+use std::{error::Error, fmt::Display};
+
+use rust_decimal::Decimal;
+use wasm_bindgen::JsValue;
+
+/// Error type for number generation.
+///
+/// ## RerollsExhausted
+///
+/// The constraints weren't satisfied after the maximum amount of rerolls.
+/// It contains the maximum amount of rerolls.
+///
+/// ## EmptyRange
+///
+/// The bounds generated by sub-expressions don't contain any possible value.
+/// It contains the generated bounds.
+///
+/// ## NumericConversion
+///
+/// A number couldn't be converted or represented while generating a number between two bounds
+/// or while operating with two numbers (like `+` or `*`).
+/// It contains the bounds or the operands.
+///
+/// ## MissingParam
+///
+/// A parameter used by the expression that wasn't supplied when generating the number.
+/// It contains the name of the parameter.
+///
+/// ## InvalidParam
+///
+/// A parameter supplied from JavaScript that isn't a number. It contains the name of the parameter.
+///
+/// ## DivisionByZero
+///
+/// A division (`/`) or remainder (`%`) where the divisor generated was 0.
+/// It contains the dividend.
+///
+/// ## MultipleValues
+///
+/// The expression has a quantifier (`(1..6){3}`) so it generates several numbers. Use `generate_many()` instead.
+///
+/// ## InvalidCount
+///
+/// The amount of repetitions of a quantifier generated was negative or too big (parameters outside of their declared bounds).
+/// It contains the amount generated.
+///
+/// ## NotEnoughValues
+///
+/// An aggregate (like `max()` or `kth()`) didn't get enough numbers, like `max((0..1){0,2})` when no number was generated.
+/// It contains the amount of numbers needed and the amount generated.
+///
+/// ## InvalidArgument
+///
+/// A built-in function got an argument it can't use, like the logarithm of 0 or a negative amount of decimals in `round()`.
+/// It contains the name of the function and the argument.
+///
+/// ## InvalidResult
+///
+/// A registered JavaScript function threw an exception or returned something that isn't a number.
+/// It contains the name of the function.
+///
+/// ## NotRepresentable
+///
+/// A generated number that can't be converted into a JavaScript number (a float). It contains the number.
+#[derive(Debug, Clone)]
+pub enum GenerateError {
+    RerollsExhausted(usize),
+    EmptyRange(Decimal, Decimal),
+    NumericConversion(Decimal, Decimal),
+    DivisionByZero(Decimal),
+    MissingParam(String),
+    InvalidParam(String),
+    MultipleValues,
+    InvalidCount(Decimal),
+    NotEnoughValues(usize, usize),
+    InvalidArgument(String, Decimal),
+    InvalidResult(String),
+    NotRepresentable(Decimal),
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerateError::RerollsExhausted(max_rerolls) => {
+                write!(f, "Rerolls exhausted. No number satisfied the constraints after {} rerolls", max_rerolls)
+            },
+            GenerateError::EmptyRange(x, y) => {
+                write!(f, "Empty range. There are no possible values between {} and {}", x, y)
+            },
+            GenerateError::NumericConversion(x, y) => {
+                write!(f, "Numeric conversion failed while generating a number between {} and {}", x, y)
+            },
+            GenerateError::DivisionByZero(dividend) => {
+                write!(f, "Division by zero. {} was divided by 0", dividend)
+            },
+            GenerateError::MissingParam(name) => {
+                write!(f, "Missing parameter. {{{}}} is used by the expression but it wasn't supplied", name)
+            },
+            GenerateError::InvalidParam(name) => {
+                write!(f, "Invalid parameter. The value of {{{}}} is not a number", name)
+            },
+            GenerateError::MultipleValues => {
+                write!(f, "Multiple values. The expression has a quantifier and generates several numbers, use generate_many()")
+            },
+            GenerateError::InvalidCount(count) => {
+                write!(f, "Invalid count. A quantifier can't repeat an expression {} times", count)
+            },
+            GenerateError::NotEnoughValues(needed, generated) => {
+                write!(f, "Not enough values. The function needed {} numbers but only {} were generated", needed, generated)
+            },
+            GenerateError::InvalidArgument(name, value) => {
+                write!(f, "Invalid argument. {}() can't be used with {}", name, value)
+            },
+            GenerateError::InvalidResult(name) => {
+                write!(f, "Invalid result. {}() didn't return a number", name)
+            },
+            GenerateError::NotRepresentable(number) => {
+                write!(f, "Not representable. {} can't be represented as a JavaScript number", number)
+            },
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+// Allows throwing generation errors to JavaScript as exceptions
+impl From<GenerateError> for JsValue {
+    fn from(error: GenerateError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
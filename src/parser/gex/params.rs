@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+/// Values of the parameters used by an expression (`{level}`). They are supplied every time a number is generated,
+/// so the same expression can be used with different inputs.
+///
+/// ```
+/// use grand::{CompileOptions, ParamDeclaration, Params};
+///
+/// let options = CompileOptions {
+///     params: vec![ParamDeclaration::new("level")],
+///     ..Default::default()
+/// };
+/// let gex = grand::compile_raw_with("({level}*10)..({level}*20)", &options).unwrap();
+///
+/// let params = Params::new().with("level", 3);
+/// let number = gex.generate_with_params(&params);
+/// assert!(number >= 30.into() && number <= 60.into());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, Decimal>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the parameters with a new value (or a replaced one)
+    pub fn with(mut self, name: &str, value: impl Into<Decimal>) -> Self {
+        self.set(name, value);
+        self
+    }
+    pub fn set(&mut self, name: &str, value: impl Into<Decimal>) {
+        self.values.insert(name.to_owned(), value.into());
+    }
+    pub fn get(&self, name: &str) -> Option<Decimal> {
+        self.values.get(name).copied()
+    }
+}
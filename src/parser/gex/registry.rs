@@ -0,0 +1,171 @@
+use std::{collections::HashMap, fmt::Debug, ops::{RangeFrom, RangeInclusive}, sync::Arc};
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Array, Function};
+use rust_decimal::Decimal;
+#[cfg(target_arch = "wasm32")]
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+use super::generate_error::GenerateError;
+
+/*
+ * Functions registered from Rust are thread-safe so compiled expressions can be shared between threads.
+ * JavaScript functions only exist in WASM, where there is a single thread.
+ */
+type NativeFunction = Arc<dyn Fn(&[Decimal]) -> Decimal + Send + Sync>;
+
+#[derive(Clone)]
+enum Callback {
+    Native(NativeFunction),
+    #[cfg(target_arch = "wasm32")]
+    Js(Function),
+}
+
+/// Functions supplied by the application that expressions can call like built-in functions (`price(10..20)`).
+/// They get the numbers generated by their arguments. The amount of arguments (arity) is checked while compiling,
+/// so functions can index the arguments they accept.
+///
+/// The bounds are the smallest and biggest results the function can give. They are the bounds of the calls,
+/// like the bounds declared for parameters. Results outside of the bounds are still accepted.
+/// A registered function replaces the built-in function or aggregate with the same name
+///
+/// ```
+/// use grand::{CompileOptions, Registry};
+/// use rust_decimal::Decimal;
+///
+/// let mut functions = Registry::new();
+/// functions.register_bounded("price", 1, 0.into(), 100.into(), |args: &[Decimal]| args[0].round_dp(2));
+/// functions.register("total", 1.., |args: &[Decimal]| args.iter().sum());
+/// let options = CompileOptions { functions, ..Default::default() };
+///
+/// let gex = grand::compile_raw_with("price(10..20)", &options).unwrap();
+/// assert_eq!(gex.max_number(), 100.into());
+/// assert!(gex.generate() >= 10.into());
+///
+/// assert!(grand::compile_raw_with("price(1, 2)", &options).is_err()); // Wrong amount of arguments
+/// assert!(grand::compile_raw_with("total()", &options).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct Registry {
+    functions: HashMap<String, HostFunction>,
+}
+
+/// Amount of arguments a registered function accepts: an exact amount (`2`) or a range (`1..=3`, `1..`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        (self.min..=self.max).contains(&count)
+    }
+}
+
+impl From<usize> for Arity {
+    fn from(count: usize) -> Self {
+        Arity { min: count, max: count }
+    }
+}
+
+impl From<RangeInclusive<usize>> for Arity {
+    fn from(range: RangeInclusive<usize>) -> Self {
+        Arity { min: *range.start(), max: *range.end() }
+    }
+}
+
+impl From<RangeFrom<usize>> for Arity {
+    fn from(range: RangeFrom<usize>) -> Self {
+        Arity { min: range.start, max: usize::MAX }
+    }
+}
+
+/// A function registered in a `Registry`
+#[derive(Clone)]
+pub struct HostFunction {
+    name: String,
+    arity: Arity,
+    min: Decimal,
+    max: Decimal,
+    callback: Callback,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a function that accepts the given amount of arguments and can give any result
+    /// (or replaces the one with the same name)
+    pub fn register(&mut self, name: &str, arity: impl Into<Arity>, function: impl Fn(&[Decimal]) -> Decimal + Send + Sync + 'static) {
+        self.register_bounded(name, arity, Decimal::MIN, Decimal::MAX, function);
+    }
+    /// Registers a function with the smallest and biggest results it can give
+    pub fn register_bounded(&mut self, name: &str, arity: impl Into<Arity>, min: Decimal, max: Decimal, function: impl Fn(&[Decimal]) -> Decimal + Send + Sync + 'static) {
+        self.insert(name, arity.into(), min, max, Callback::Native(Arc::new(function)));
+    }
+
+    // Registers a JavaScript function, see HostFunction::call()
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn register_js(&mut self, name: &str, arity: Arity, min: Decimal, max: Decimal, function: Function) {
+        self.insert(name, arity, min, max, Callback::Js(function));
+    }
+
+    fn insert(&mut self, name: &str, arity: Arity, min: Decimal, max: Decimal, callback: Callback) {
+        self.functions.insert(name.to_owned(), HostFunction { name: name.to_owned(), arity, min, max, callback });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HostFunction> {
+        self.functions.get(name)
+    }
+}
+
+impl HostFunction {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Whether the function can be called with this amount of arguments
+    pub fn accepts(&self, count: usize) -> bool {
+        self.arity.accepts(count)
+    }
+    /// Smallest and biggest results declared for the function
+    pub fn bounds(&self) -> (Decimal, Decimal) {
+        (self.min, self.max)
+    }
+    /// Calls the function with the numbers generated by the arguments.
+    /// JavaScript functions get them as numbers (`price(12.5)`), their exceptions and results that aren't numbers
+    /// are returned as `GenerateError::InvalidResult`
+    pub fn call(&self, args: &[Decimal]) -> Result<Decimal, GenerateError> {
+        match &self.callback {
+            Callback::Native(function) => Ok(function(args)),
+            #[cfg(target_arch = "wasm32")]
+            Callback::Js(function) => {
+                let js_args: Array = args.iter().map(|arg| JsValue::from_f64(arg.to_f64().unwrap_or(f64::NAN))).collect();
+                function.apply(&JsValue::NULL, &js_args).ok()
+                    .and_then(|result| result.as_f64())
+                    .and_then(Decimal::from_f64)
+                    .ok_or_else(|| GenerateError::InvalidResult(self.name.clone()))
+            },
+        }
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.functions.values()).finish()
+    }
+}
+
+impl Debug for HostFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .finish()
+    }
+}
@@ -1,31 +1,35 @@
 use std::str::Chars;
 
-use super::{token::Token, token_type::TokenType};
+use super::{diagnostic::{Diagnostic, Span}, parse_error::CompilerError, token::Token, token_type::TokenType};
 
 struct Lexer<'a> {
     source_chars: Chars<'a>,
     source_char_count: usize,
-    lexeme_start: usize,
+    source_len: usize,
+    lexeme_start: usize, // Byte offset where the token being read starts
     char_reading: usize,
+    byte_reading: usize,
     char_reading_value: char,
     line: usize,
     column: usize,
+    strict: bool, // Unknown characters are errors instead of being ignored
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> Lexer<'a> {
     // tokenization
-    fn tokenize(mut self) -> Vec<Token> {
+    fn tokenize(mut self) -> (Vec<Token>, Vec<Diagnostic>) {
         let mut tokens: Vec<Token> = Vec::new();
 
         while !self.is_eof() {
-            self.lexeme_start = self.char_reading;
+            self.lexeme_start = self.byte_reading;
             let t = self.scan_token();
             if t.token_type != TokenType::Ignored {
                 tokens.push(t);
             }
         }
 
-        tokens
+        (tokens, self.diagnostics)
     }
     
     // big helper functions
@@ -37,6 +41,7 @@ impl<'a> Lexer<'a> {
             content: c.to_string(), // Default
             line: self.line,
             column: self.column,
+            span: Span::default(),
         };
 
         match c {
@@ -46,9 +51,40 @@ impl<'a> Lexer<'a> {
             ']' => token.token_type = TokenType::RBrack,
             '(' => token.token_type = TokenType::LParen,
             ')' => token.token_type = TokenType::RParen,
+            // Modifiers of selections (`|uniform`)
+            '|' if self.peek().is_ascii_alphabetic() => {
+                token.token_type = TokenType::Modifier;
+                token.content = self.read_modifier();
+            }
             '|' => token.token_type = TokenType::Constraint,
-            // Constraint Types
+            // Constraint Types (and multiplication)
             '*' => token.token_type = TokenType::CMultOf,
+            // Arithmetic
+            '+' => token.token_type = TokenType::Plus,
+            '-' => token.token_type = TokenType::Minus,
+            '%' => token.token_type = TokenType::Percent,
+            // Bindings
+            '=' => token.token_type = TokenType::Equals,
+            ';' => token.token_type = TokenType::Semicolon,
+            '$' => {
+                if self.is_identifier(self.peek()) {
+                    token.token_type = TokenType::Binding;
+                    token.content = self.read_identifier();
+                } else {
+                    self.unknown_character(c, &token);
+                }
+            }
+            // Weights of the entries of a selection (`:70`)
+            ':' if self.is_digit(self.peek()) || matches!(self.peek(), '.' | '-' | '+') => {
+                token.token_type = TokenType::Weight;
+                token.content = self.read_weight();
+            }
+            // Precision
+            ':' | '@' => {
+                let token_info = self.read_precision();
+                token.token_type = token_info.0;
+                token.content = token_info.1;
+            }
 
             // Ranges
             ',' => {
@@ -72,27 +108,61 @@ impl<'a> Lexer<'a> {
                     token.content = token_info.1;
                 }
                 // If this is not a range we just ignore the token.
-                // Grand Expressions are just chill like that.
+                // Grand Expressions are just chill like that (unless we are in strict mode).
+                else {
+                    self.unknown_character(c, &token);
+                }
+            }
+
+            // Dice without an amount (`d20`)
+            'd' if self.is_digit(self.peek()) => {
+                token.token_type = TokenType::Dice;
+                token.content = self.read_dice(c.to_string());
             }
 
+            // Parameters (`{level}`) and quantifiers (`{3}`)
+            '{' => {
+                match self.read_param() {
+                    Some(name) => {
+                        token.token_type = TokenType::Param;
+                        token.content = name;
+                    },
+                    None => token.token_type = TokenType::LBrace,
+                }
+            }
+            '}' => token.token_type = TokenType::RBrace,
+
+            // Comments
+            '#' => self.skip_line_comment(),
+            '/' if self.peek() == '*' => self.skip_block_comment(&token),
+            '/' => token.token_type = TokenType::Slash,
+
             // New Lines
             '\n' => {
                 self.line += 1;
                 self.column = 0;
             }
+            // Whitespace is always ignored
+            ' ' | '\t' | '\r' => {}
 
-            // Any letter, space, etc. is ignored
+            // Any letter, symbol, etc. is ignored
             // We only have to check for numbers
             _ => {
-                if self.is_digit(c) || c == '-' || c == '+' {
+                if self.is_digit(c) {
                     let token_info = self.read_numeric();
                     token.token_type = token_info.0;
                     token.content = token_info.1;
+                } else if let Some(name) = self.read_function_name() {
+                    token.token_type = TokenType::Identifier;
+                    token.content = name;
+                } else {
+                    // If this is not a number we ignore the token
+                    self.unknown_character(c, &token);
                 }
-                // If this is not a number we ignore the token
             }
         }
-        
+
+        token.span = Span::new(self.lexeme_start, self.byte_reading);
         token
     }
 
@@ -117,6 +187,11 @@ impl<'a> Lexer<'a> {
         while self.is_digit(self.peek()) {
             number.push(self.advance());
         }
+        // An amount of dice (`3d6`)
+        if self.peek() == 'd' && self.is_digit(self.peek_next()) {
+            number.push(self.advance());
+            return (TokenType::Dice, self.read_dice(number))
+        }
         // If we reached a dot and there are more numbers afterwards we take it and continue reading
         if self.peek() == '.' && self.is_digit(self.peek_next()) {
             number.push(self.advance());
@@ -129,12 +204,162 @@ impl<'a> Lexer<'a> {
         (TokenType::Number, number)
     }
 
+    // Sides of the dice (the `d` was already read), followed by `!` if they explode and
+    // the amount of dice to keep (`kh3`, `kl3`) or drop (`dh3`, `dl3`). `!` can also be at the end
+    fn read_dice(&mut self, mut dice: String) -> String {
+        while self.is_digit(self.peek()) {
+            dice.push(self.advance());
+        }
+        if self.peek() == '!' {
+            dice.push(self.advance());
+        }
+        if matches!(self.peek(), 'k' | 'd') && matches!(self.peek_next(), 'h' | 'l') {
+            dice.push(self.advance());
+            dice.push(self.advance());
+            while self.is_digit(self.peek()) {
+                dice.push(self.advance());
+            }
+            if self.peek() == '!' {
+                dice.push(self.advance());
+            }
+        }
+        dice
+    }
+
+    // `:` is followed by the name of a type (`:int`) and `@` by the amount of decimals (`@3`)
+    fn read_precision(&mut self) -> (TokenType, String) {
+        let mut precision = self.char_reading_value.to_string();
+        if self.char_reading_value == ':' {
+            while self.peek().is_ascii_alphabetic() {
+                precision.push(self.advance());
+            }
+        } else {
+            while self.is_digit(self.peek()) {
+                precision.push(self.advance());
+            }
+        }
+
+        (TokenType::Precision, precision)
+    }
+
+    // The weight is a number (the `:` is not part of it). The sign is kept so that negative weights can be reported
+    fn read_weight(&mut self) -> String {
+        let mut weight = String::new();
+        if matches!(self.peek(), '-' | '+') {
+            weight.push(self.advance());
+        }
+        while self.is_digit(self.peek()) || self.peek() == '.' {
+            weight.push(self.advance());
+        }
+        weight
+    }
+
+    // The name of a modifier (the `|` is not part of it)
+    fn read_modifier(&mut self) -> String {
+        let mut modifier = String::new();
+        while self.peek().is_ascii_alphabetic() {
+            modifier.push(self.advance());
+        }
+        modifier
+    }
+
+    // Names of bindings. The `$` is part of the name
+    fn read_identifier(&mut self) -> String {
+        let mut identifier = self.char_reading_value.to_string();
+        while self.is_identifier(self.peek()) {
+            identifier.push(self.advance());
+        }
+        identifier
+    }
+
+    // Names of parameters between braces (`{level}`). The content of the token is the name.
+    // Nothing is consumed if this is not a parameter
+    fn read_param(&mut self) -> Option<String> {
+        let mut chars = self.source_chars.clone();
+        let mut name = String::new();
+        loop {
+            match chars.next() {
+                Some('}') if !name.is_empty() => break,
+                Some(c) if self.is_identifier(c) && !(name.is_empty() && self.is_digit(c)) => name.push(c),
+                _ => return None,
+            }
+        }
+        // Consume the name and the closing brace
+        for _ in 0..=name.len() {
+            self.advance();
+        }
+        Some(name)
+    }
+
+    // Names of functions (`sum(`). Words that are not followed by a parenthesis are ignored like any other letter
+    fn read_function_name(&mut self) -> Option<String> {
+        if !self.char_reading_value.is_ascii_alphabetic() {
+            return None
+        }
+        let mut name = self.char_reading_value.to_string();
+        let mut chars = self.source_chars.clone();
+        loop {
+            match chars.next() {
+                Some('(') => break,
+                Some(c) if self.is_identifier(c) => name.push(c),
+                _ => return None,
+            }
+        }
+        // Consume the rest of the name, the parenthesis is a token of its own
+        for _ in 1..name.len() {
+            self.advance();
+        }
+        Some(name)
+    }
+
+    // Comments run until the end of the line
+    fn skip_line_comment(&mut self) {
+        while !self.is_eof() && self.peek() != '\n' {
+            self.advance();
+        }
+    }
+
+    // Block comments run until the closing `*/`, they can take multiple lines
+    fn skip_block_comment(&mut self, start: &Token) {
+        self.advance(); // Skip the '*' of the opening "/*"
+        loop {
+            if self.is_eof() {
+                self.diagnostics.push(Diagnostic::error(
+                    CompilerError::UnterminatedComment(start.line, start.column),
+                    Span::new(self.lexeme_start, self.byte_reading)
+                ));
+                return
+            }
+            let c = self.advance();
+            if c == '*' && self.peek() == '/' {
+                self.advance();
+                return
+            }
+            if c == '\n' {
+                self.line += 1;
+                self.column = 0;
+            }
+        }
+    }
+
+    fn unknown_character(&mut self, c: char, token: &Token) {
+        if self.strict {
+            self.diagnostics.push(Diagnostic::error(
+                CompilerError::UnknownCharacter(c, token.line, token.column),
+                Span::new(self.lexeme_start, self.byte_reading)
+            ));
+        }
+    }
+
     // Small helper functions
     fn is_eof(&self) -> bool {
-        return self.char_reading >= self.source_char_count
+        self.char_reading >= self.source_char_count
     }
     fn is_digit(&self, c: char) -> bool {
-        c.is_digit(10)
+        c.is_ascii_digit()
+    }
+    fn is_identifier(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
     }
     fn peek(&self) -> char {
         self.source_chars
@@ -147,9 +372,7 @@ impl<'a> Lexer<'a> {
     fn peek_next(&self) -> char {
         let mut clone_chars = self.source_chars.clone();
         // Advance 1
-        clone_chars.next().unwrap_or_else(|| {
-            return '\0';
-        });
+        clone_chars.next();
         // Advance a second time
         clone_chars.next().unwrap_or('\0')
     }
@@ -157,21 +380,26 @@ impl<'a> Lexer<'a> {
         self.column += 1;
         self.char_reading += 1;
         self.char_reading_value = self.source_chars.next().unwrap_or('\n');
+        self.byte_reading = self.source_len - self.source_chars.as_str().len();
         self.char_reading_value
     }
 
 }
 
 // API
-pub fn tokenize(source: &str) -> Vec<Token> {
+pub fn tokenize(source: &str, strict: bool) -> (Vec<Token>, Vec<Diagnostic>) {
     let lexer = Lexer {
         source_chars: source.chars(),
         source_char_count: source.chars().count(),
+        source_len: source.len(),
         lexeme_start: 0,
         char_reading: 0,
+        byte_reading: 0,
         char_reading_value: '\0',
         line: 0,
-        column: 0
+        column: 0,
+        strict,
+        diagnostics: Vec::new(),
     };
 
     lexer.tokenize()
@@ -181,7 +409,8 @@ pub fn tokenize(source: &str) -> Vec<Token> {
 pub fn print_tokens(tokens: &Vec<Token>) {
     for token in tokens {
         let token_color = match token.token_type {
-            TokenType::Number => "\x1b[38;5;230m",
+            TokenType::Number |
+            TokenType::Dice => "\x1b[38;5;230m",
             TokenType::RangeCC => "\x1b[38;5;42m",
             TokenType::RangeOO => "\x1b[38;5;42m",
             TokenType::RangeCO => "\x1b[38;5;42m",
@@ -192,8 +421,22 @@ pub fn print_tokens(tokens: &Vec<Token>) {
             TokenType::RBrack => "\x1b[38;5;33m",
             TokenType::LParen => "\x1b[38;5;141m",
             TokenType::RParen => "\x1b[38;5;141m",
-            TokenType::Constraint => "\x1b[38;5;209m",
+            TokenType::LBrace |
+            TokenType::RBrace => "\x1b[38;5;213m",
+            TokenType::Constraint |
+            TokenType::Modifier => "\x1b[38;5;209m",
             TokenType::CMultOf => "\x1b[38;5;195m",
+            TokenType::Precision => "\x1b[38;5;219m",
+            TokenType::Weight => "\x1b[38;5;230m",
+            TokenType::Plus |
+            TokenType::Minus |
+            TokenType::Slash |
+            TokenType::Percent => "\x1b[38;5;195m",
+            TokenType::Binding |
+            TokenType::Param => "\x1b[38;5;117m",
+            TokenType::Identifier => "\x1b[38;5;229m",
+            TokenType::Equals |
+            TokenType::Semicolon => "\x1b[0m",
             TokenType::Ignored => "\x1b[0m",
         };
 
@@ -1,19 +1,181 @@
 use std::{error::Error, fmt::Display};
 
+use rust_decimal::Decimal;
+
 use super::token_type::TokenType;
 
 /// Error type for parser errors.
-/// 
+///
 /// ## UnexpectedToken
-/// 
+///
 /// It has 4 arguments:
 /// - `Vec<TokenType>` with the expected token(s)
 /// - `TokenType` with the actual token in the code
 /// - `usize` with the line where the error happened (most likely one since expressions are short)
 /// - `usize` with the column where the error happened
+///
+/// ## UnbalancedDelimiter
+///
+/// A parenthesis or bracket that is never closed (or a closing one that was never opened).
+/// It contains the delimiter and its line and column.
+///
+/// ## DanglingConstraint
+///
+/// A constraint (`|`, `|*`, `|!*`) that doesn't have any values, like `0..10|*`.
+/// It contains the line and column of the pipe.
+///
+/// ## InvalidConstraint
+///
+/// A constraint value that can't be used, like `0..10|*0`.
+/// It contains the value and its line and column.
+///
+/// ## EmptySupport
+///
+/// A range without any possible values. Constraints that are incompatible, like `0..10|*2|!*2`, contain the line and column
+/// of the first constraint. Ranges that are always empty because of their bounds, like `5..3`, `0,,0` or `0,,10|*10`,
+/// contain the line and column of the range.
+///
+/// ## EmptySelection
+///
+/// A selection without entries (`[]`). It contains the line and column of the opening bracket.
+///
+/// ## UnterminatedRange
+///
+/// A range operator followed by something that can't be its upper bound, like `0..!`.
+/// It contains the token found after the range and the line and column of the range operator.
+///
+/// ## UnknownCharacter
+///
+/// A character that isn't part of the language, only reported in strict mode.
+/// It contains the character and its line and column.
+///
+/// ## UnterminatedComment
+///
+/// A block comment (`/*`) that is never closed. It contains the line and column where it starts.
+///
+/// ## JuxtaposedExpression
+///
+/// An expression that starts right after another one ended, like `5 7` or `0..10 3`.
+/// It contains the line and column where the second expression starts.
+///
+/// ## UnconsumedTokens
+///
+/// Tokens left after the end of the program that aren't part of the expression.
+/// It contains the first unconsumed token and its line and column.
+///
+/// ## InvalidNumber
+///
+/// A numerical token that can't be represented, like a number that is too big.
+/// It contains the text of the token and its line and column.
+///
+/// ## MissingOperand
+///
+/// An operator (`+`, `-`, `*`, `/`, `%`) that isn't followed by a number, selection or sub-expression, like `5+`.
+/// It contains the operator and its line and column.
+///
+/// ## DivisionByZero
+///
+/// A division (`/`) or remainder (`%`) by a constant 0, like `(0..10)/0`.
+/// It contains the line and column of the operator.
+///
+/// ## UnknownBinding
+///
+/// A binding that is used before being defined (or never defined), like `$a` in `$a..10`.
+/// It contains the name of the binding and its line and column.
+///
+/// ## DuplicateBinding
+///
+/// A binding that is defined twice, like `$a = 0..1; $a = 2..3; $a`.
+/// It contains the name of the binding and the line and column of the second definition.
+///
+/// ## UnknownParam
+///
+/// A parameter (`{level}`) that isn't declared in the `CompileOptions`.
+/// It contains the name of the parameter and its line and column.
+///
+/// ## InvalidPrecision
+///
+/// A precision that doesn't exist, like `:float` or `@29` (Decimals can't have more than 28 decimals).
+/// It contains the text of the token and its line and column.
+///
+/// ## InvalidWeight
+///
+/// A weight of an entry in a selection that isn't a non-negative number, like `[1:-5, 2:1]`.
+/// It's also reported when the weights are too big to be compared exactly (their mantissas have to fit in 96 bits
+/// once they all have the same amount of decimals).
+/// It contains the text of the weight and its line and column.
+///
+/// ## ZeroWeights
+///
+/// A weighted selection whose weights are all zero, like `[1:0, 2:0]`. It contains the line and column of the opening bracket.
+///
+/// ## InvalidDice
+///
+/// Dice that can't be rolled, like `0d6`, `3d0`, `4d6kh5` (keeping more dice than there are), `4d6dl4`
+/// (dropping every die), `3d1!` (a die with one side would explode forever) or `1000000d6` (more dice or sides
+/// than the maximum, `gex::dice::MAX_DICE`).
+/// It contains the text of the dice and their line and column.
+///
+/// ## MisplacedQuantifier
+///
+/// A quantifier (`{3}`) that repeats something that has to be a single number, like a sub-expression, an entry in a
+/// selection or the definition of a binding. Quantifiers can only repeat the whole expression or an argument of an aggregate (`sum((1..6){3})`).
+/// It contains the line and column of the quantifier.
+///
+/// ## InvalidQuantifier
+///
+/// A quantifier whose count can be negative or bigger than the maximum amount of repetitions (`gex::MAX_REPETITIONS`),
+/// or whose minimum count is always bigger than its maximum (`{5,2}`). It contains the line and column of the quantifier.
+///
+/// ## UnknownFunction
+///
+/// A call to a function that doesn't exist, like `foo(1)`. It contains the name of the function and its line and column.
+///
+/// ## WrongArgumentCount
+///
+/// A function called with the wrong amount of arguments, like `sum()`.
+/// It contains the name of the function, the amount of arguments and the line and column of the function.
+///
+/// ## InvalidArgument
+///
+/// An argument that a function can't use, like `kth(0.5, (1..6){3})` (the position has to be a constant integer greater than 0)
+/// or `log(-5..0)` (the logarithm of a number that is never positive).
+/// It contains the name of the function and the line and column of the argument.
+///
+/// ## InvalidModifier
+///
+/// A modifier that doesn't exist (`[1, 2]|random`) or that can't be used where it is, like `|uniform`
+/// after something that is not a selection or after a selection with weights.
+/// It contains the name of the modifier and its line and column.
 #[derive(Debug, Clone)]
 pub enum CompilerError {
     UnexpectedToken(Vec<TokenType>, TokenType, usize, usize),
+    UnbalancedDelimiter(TokenType, usize, usize),
+    DanglingConstraint(usize, usize),
+    InvalidConstraint(Decimal, usize, usize),
+    EmptySupport(usize, usize),
+    EmptySelection(usize, usize),
+    UnterminatedRange(TokenType, usize, usize),
+    UnknownCharacter(char, usize, usize),
+    UnterminatedComment(usize, usize),
+    JuxtaposedExpression(usize, usize),
+    UnconsumedTokens(TokenType, usize, usize),
+    InvalidNumber(String, usize, usize),
+    MissingOperand(TokenType, usize, usize),
+    DivisionByZero(usize, usize),
+    UnknownBinding(String, usize, usize),
+    DuplicateBinding(String, usize, usize),
+    UnknownParam(String, usize, usize),
+    InvalidPrecision(String, usize, usize),
+    InvalidWeight(String, usize, usize),
+    ZeroWeights(usize, usize),
+    InvalidDice(String, usize, usize),
+    InvalidModifier(String, usize, usize),
+    UnknownFunction(String, usize, usize),
+    WrongArgumentCount(String, usize, usize, usize),
+    InvalidArgument(String, usize, usize),
+    MisplacedQuantifier(usize, usize),
+    InvalidQuantifier(usize, usize),
     NoExpressions
 }
 
@@ -23,6 +185,84 @@ impl Display for CompilerError {
             CompilerError::UnexpectedToken(valid_tokens, actual_token, line, column) => {
                 write!(f, "Unexpected Token in line {}, column {}. Expected one of {:?}, found {:?}", line, column, valid_tokens, actual_token)
             },
+            CompilerError::UnbalancedDelimiter(delimiter, line, column) => {
+                write!(f, "Unbalanced delimiter in line {}, column {}. {:?} has no matching pair", line, column, delimiter)
+            },
+            CompilerError::DanglingConstraint(line, column) => {
+                write!(f, "Dangling constraint in line {}, column {}. Expected a constraint type followed by its values", line, column)
+            },
+            CompilerError::InvalidConstraint(value, line, column) => {
+                write!(f, "Invalid constraint value in line {}, column {}. {} can't be used in a constraint", line, column, value)
+            },
+            CompilerError::EmptySupport(line, column) => {
+                write!(f, "Empty range in line {}, column {}. No number in the range can satisfy its bounds and every constraint", line, column)
+            },
+            CompilerError::EmptySelection(line, column) => {
+                write!(f, "Empty selection in line {}, column {}. Selections need at least one entry", line, column)
+            },
+            CompilerError::UnterminatedRange(actual_token, line, column) => {
+                write!(f, "Unterminated range in line {}, column {}. Expected an upper bound, found {:?}", line, column, actual_token)
+            },
+            CompilerError::UnknownCharacter(character, line, column) => {
+                write!(f, "Unknown character in line {}, column {}. {:?} is not part of the language", line, column, character)
+            },
+            CompilerError::UnterminatedComment(line, column) => {
+                write!(f, "Unterminated comment in line {}, column {}. Expected \"*/\" before the end of the program", line, column)
+            },
+            CompilerError::JuxtaposedExpression(line, column) => {
+                write!(f, "Unexpected expression in line {}, column {}. The previous expression already ended here, expected a range, an operator or the end of the expression", line, column)
+            },
+            CompilerError::UnconsumedTokens(actual_token, line, column) => {
+                write!(f, "Unconsumed tokens in line {}, column {}. Found {:?} after the end of the expression", line, column, actual_token)
+            },
+            CompilerError::InvalidNumber(content, line, column) => {
+                write!(f, "Invalid number in line {}, column {}. \"{}\" is not a valid number", line, column, content)
+            },
+            CompilerError::MissingOperand(operator, line, column) => {
+                write!(f, "Missing operand in line {}, column {}. {:?} must be followed by a number, selection or sub-expression", line, column, operator)
+            },
+            CompilerError::DivisionByZero(line, column) => {
+                write!(f, "Division by zero in line {}, column {}. The divisor is always 0", line, column)
+            },
+            CompilerError::UnknownBinding(name, line, column) => {
+                write!(f, "Unknown binding in line {}, column {}. {} is not defined before this point", line, column, name)
+            },
+            CompilerError::DuplicateBinding(name, line, column) => {
+                write!(f, "Duplicate binding in line {}, column {}. {} was already defined", line, column, name)
+            },
+            CompilerError::UnknownParam(name, line, column) => {
+                write!(f, "Unknown parameter in line {}, column {}. {{{}}} is not declared in the compile options", line, column, name)
+            },
+            CompilerError::InvalidPrecision(content, line, column) => {
+                write!(f, "Invalid precision in line {}, column {}. \"{}\" is not a valid precision, expected \":int\" or \"@\" followed by an amount of decimals (0 to 28)", line, column, content)
+            },
+            CompilerError::InvalidWeight(content, line, column) => {
+                write!(f, "Invalid weight in line {}, column {}. \"{}\" is not a valid weight, expected a non-negative number", line, column, content)
+            },
+            CompilerError::ZeroWeights(line, column) => {
+                write!(f, "Invalid weights in selection in line {}, column {}. At least one entry needs a weight greater than zero", line, column)
+            },
+            CompilerError::InvalidDice(content, line, column) => {
+                write!(f, "Invalid dice in line {}, column {}. \"{}\" can't be rolled, expected between 1 and {} dice with between 1 and {} sides, and between 1 and all the dice kept", line, column, content, crate::parser::gex::dice::MAX_DICE, crate::parser::gex::dice::MAX_DICE)
+            },
+            CompilerError::InvalidModifier(name, line, column) => {
+                write!(f, "Invalid modifier in line {}, column {}. \"|{}\" can't be used here, expected \"|uniform\" after a selection without weights", line, column, name)
+            },
+            CompilerError::UnknownFunction(name, line, column) => {
+                write!(f, "Unknown function in line {}, column {}. \"{}\" is not a function", line, column, name)
+            },
+            CompilerError::WrongArgumentCount(name, count, line, column) => {
+                write!(f, "Wrong amount of arguments in line {}, column {}. {}() can't be called with {} arguments", line, column, name, count)
+            },
+            CompilerError::InvalidArgument(name, line, column) => {
+                write!(f, "Invalid argument in line {}, column {}. This argument can't be used by {}()", line, column, name)
+            },
+            CompilerError::MisplacedQuantifier(line, column) => {
+                write!(f, "Misplaced quantifier in line {}, column {}. Quantifiers generate several numbers, they can only repeat the whole expression or an argument of sum(), min(), max(), avg() or kth()", line, column)
+            },
+            CompilerError::InvalidQuantifier(line, column) => {
+                write!(f, "Invalid quantifier in line {}, column {}. The amount of repetitions must be between 0 and {}", line, column, crate::parser::gex::MAX_REPETITIONS)
+            },
             CompilerError::NoExpressions => {
                 write!(f, "No expressions or sub-expressions in program")
             }
@@ -0,0 +1,44 @@
+use std::fmt::Display;
+
+use rust_decimal::Decimal;
+
+/// Warning type for the parser. Warnings don't stop the compilation, they
+/// point out things in the program that may not work as expected.
+///
+/// ## PrecalculationSkipped
+///
+/// A constrained range that is too big to store its possible values within the memory budget.
+/// Its constraints are checked by rerolling numbers instead.
+/// It contains the bytes the range would need, the memory budget and the line and column of the constraints.
+///
+/// ## RerolledConstraint
+///
+/// A `!*` constraint that is checked by generating numbers until one of them is valid.
+/// It contains the maximum amount of rerolls and the line and column of the constraints.
+///
+/// ## UnusedBinding
+///
+/// A binding that is defined but never used. It's still generated.
+/// It contains the name of the binding and the line and column of its definition.
+#[derive(Debug, Clone)]
+pub enum CompilerWarning {
+    PrecalculationSkipped(Decimal, usize, usize, usize),
+    RerolledConstraint(usize, usize, usize),
+    UnusedBinding(String, usize, usize),
+}
+
+impl Display for CompilerWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompilerWarning::PrecalculationSkipped(bytes, budget, line, column) => {
+                write!(f, "Precalculation skipped in line {}, column {}. The range needs up to {} bytes but the budget is {} bytes", line, column, bytes.round(), budget)
+            },
+            CompilerWarning::RerolledConstraint(max_rerolls, line, column) => {
+                write!(f, "Rerolled constraint in line {}, column {}. Numbers are generated until they satisfy the constraint, this gives up after {} rerolls", line, column, max_rerolls)
+            },
+            CompilerWarning::UnusedBinding(name, line, column) => {
+                write!(f, "Unused binding in line {}, column {}. {} is never used", line, column, name)
+            },
+        }
+    }
+}
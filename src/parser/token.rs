@@ -1,9 +1,10 @@
-use super::token_type::TokenType;
+use super::{diagnostic::Span, token_type::TokenType};
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub content: String,
     pub line: usize,
-    pub column: usize
+    pub column: usize,
+    pub span: Span
 }
\ No newline at end of file
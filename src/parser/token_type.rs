@@ -2,6 +2,7 @@
 pub enum TokenType {
     // Numbers and ranges
     Number,
+    Dice, // `3d6`, `4d6kh3`, `2d10!`
     RangeCC,
     RangeOO,
     RangeCO,
@@ -14,12 +15,34 @@ pub enum TokenType {
     RBrack,
     LParen,
     RParen,
+    LBrace, // Quantifiers (`{3}`)
+    RBrace,
     Constraint,
-    // Constraints
+    // Constraints (also the multiplication operator)
     CMultOf,
+    // Arithmetic operators
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    // Bindings (`$a = 0..10; $a..($a+5)`)
+    Binding,
+    Equals,
+    Semicolon,
+    // Runtime parameters (`{level}`)
+    Param,
+    // Names of functions (`sum(...)`)
+    Identifier,
+    // Type or amount of decimals of the result (`:int`, `@3`)
+    Precision,
+    // Weight of an entry in a selection (`[1:70, 2:30]`)
+    Weight,
+    // Changes how a selection picks its entries (`[0..1, 10..1000]|uniform`)
+    Modifier,
 
-    // Anything that is not valid is ignored.
-    // This allows you to write comments as long
-    // as you only use letters and only a few symbols
+    // Whitespace and comments are ignored.
+    // Outside of strict mode, anything that is not valid is ignored
+    // too. This allows you to write comments as long as you only
+    // use letters and only a few symbols
     Ignored
 }
\ No newline at end of file
@@ -1,32 +1,134 @@
 use rust_decimal::{prelude::FromPrimitive, prelude::ToPrimitive, Decimal};
 
-use crate::rng_traits::Randomizable;
+use crate::parser::gex::generate_error::GenerateError;
+use crate::rng_traits::{GrandRng, Randomizable};
 
-pub fn random_usize(min: usize, max: usize) -> usize {
-    let rand_base = usize::random();
-    let range = max - min;
+/*
+ * Unbiased random number from 0 (inclusive) to range (exclusive).
+ * Lemire's method: the random number is multiplied by the range and the high 64 bits
+ * are the result. The few low values that would make some results more likely are rejected.
+ */
+fn random_below(rng: &mut dyn GrandRng, range: u64) -> u64 {
+    let mut multiplied = u64::random_with(rng) as u128 * range as u128;
+    let mut low = multiplied as u64;
+    if low < range {
+        // 2^64 % range, computed without 128 bit divisions
+        let threshold = range.wrapping_neg() % range;
+        while low < threshold {
+            multiplied = u64::random_with(rng) as u128 * range as u128;
+            low = multiplied as u64;
+        }
+    }
+    (multiplied >> 64) as u64
+}
+
+/*
+ * Same as random_below but for ranges that don't fit in 64 bits (Decimal mantissas are 96 bits).
+ * Numbers are masked to the bits of the range and rejected if they are outside of it.
+ */
+pub fn random_below_u128(rng: &mut dyn GrandRng, range: u128) -> u128 {
+    if range <= u64::MAX as u128 {
+        return random_below(rng, range as u64) as u128
+    }
+    let mask = u128::MAX >> (range - 1).leading_zeros();
+    loop {
+        let number = u128::random_with(rng) & mask;
+        if number < range {
+            return number
+        }
+    }
+}
+
+// From min (inclusive) to max (exclusive). Returns min if the range is empty.
+// Computed with 64 bit numbers so 32 bit platforms (like WASM) get the same results
+pub fn random_usize(rng: &mut dyn GrandRng, min: usize, max: usize) -> usize {
+    if max <= min {
+        return min
+    }
+    min + random_below(rng, (max - min) as u64) as usize
+}
+
+// Largest mantissa a Decimal can store (96 bits)
+const MAX_MANTISSA: i128 = (1 << 96) - 1;
 
-    let proportion_unit = usize::MAX / range;
-    let proportional_rand = rand_base / proportion_unit;
+/*
+ * Value as an integer at the given scale (value * 10^scale).
+ * Values with more digits than the scale are rounded up (ceil) or down (floor).
+ * Returns None if the integer is too big.
+ */
+fn to_lattice(value: Decimal, scale: u32, ceil: bool) -> Option<i128> {
+    let mantissa = value.mantissa();
+    let value_scale = value.scale();
+    if scale >= value_scale {
+        mantissa.checked_mul(10i128.checked_pow(scale - value_scale)?)
+    } else {
+        let divisor = 10i128.pow(value_scale - scale);
+        let quotient = mantissa.div_euclid(divisor);
+        let exact = mantissa.rem_euclid(divisor) == 0;
+        Some(if ceil && !exact { quotient + 1 } else { quotient })
+    }
+}
 
-    proportional_rand + min
+/*
+ * Biggest scale at which both numbers can be stored in a Decimal.
+ * This is the most precise scale we can use to generate numbers between them
+ */
+fn infer_scale(min: Decimal, max: Decimal) -> u32 {
+    let fits = |value: Decimal, scale: u32| {
+        to_lattice(value, scale, false).is_some_and(|number| number.abs() <= MAX_MANTISSA)
+    };
+    (0..=Decimal::MAX_SCALE)
+        .rev()
+        .find(|scale| fits(min, *scale) && fits(max, *scale))
+        .unwrap_or(0)
 }
 
-pub fn random_decimal(min: Decimal, max: Decimal) -> Decimal {
-    let rand_base = u128::random();
-    let range: f64 = (max - min).to_f64().unwrap();
+/*
+ * Uniformly distributed number from min to max with `scale` decimals.
+ * If there's no scale, the most precise one is used and the trailing zeros are removed.
+ * The number is generated as an integer (mantissa), so it's exact no matter how big or small the range is.
+ *
+ * Open bounds exclude exactly the bound: numbers are generated from the next (or previous)
+ * number with `scale` decimals.
+ */
+pub fn random_decimal(rng: &mut dyn GrandRng, min: Decimal, max: Decimal, min_open: bool, max_open: bool, scale: Option<u32>) -> Result<Decimal, GenerateError> {
+    let conversion_error = || GenerateError::NumericConversion(min, max);
+    let lattice_scale = scale.unwrap_or_else(|| infer_scale(min, max));
+    let to_decimal = |number: i128| Decimal::try_from_i128_with_scale(number, lattice_scale).ok();
 
-    let proportion_unit = (u128::MAX as f64) / range;
-    let proportional_rand: Decimal = Decimal::from_f64(rand_base as f64 / proportion_unit).unwrap();
+    let mut low = to_lattice(min, lattice_scale, true).ok_or_else(conversion_error)?;
+    let mut high = to_lattice(max, lattice_scale, false).ok_or_else(conversion_error)?;
+    if low.abs() > MAX_MANTISSA || high.abs() > MAX_MANTISSA {
+        return Err(conversion_error())
+    }
+    if min_open && to_decimal(low) == Some(min) {
+        low += 1;
+    }
+    if max_open && to_decimal(high) == Some(max) {
+        high -= 1;
+    }
+    if high < low {
+        return Err(GenerateError::EmptyRange(min, max))
+    }
 
-    proportional_rand + min
+    let offset = random_below_u128(rng, (high - low) as u128 + 1) as i128;
+    let number = to_decimal(low + offset).ok_or_else(conversion_error)?;
+    if scale.is_some() {
+        Ok(number)
+    } else {
+        Ok(number.normalize())
+    }
 }
-pub fn random_decimal_int(min: Decimal, max: Decimal) -> Decimal {
-    let rand_base = usize::random();
-    let range: usize = (max - min).to_usize().unwrap();
 
-    let proportion_unit = usize::MAX / range;
-    let proportional_rand: Decimal = Decimal::from_usize(rand_base as usize / proportion_unit).unwrap();
+// Integer from min to max (both inclusive)
+pub fn random_decimal_int(rng: &mut dyn GrandRng, min: Decimal, max: Decimal) -> Result<Decimal, GenerateError> {
+    let conversion_error = || GenerateError::NumericConversion(min, max);
+    let (low, high) = (min.ceil(), max.floor());
+    if high < low {
+        return Err(GenerateError::EmptyRange(min, max))
+    }
+    let range = high.checked_sub(low).and_then(|range| range.to_u128()).ok_or_else(conversion_error)? + 1;
 
-    proportional_rand + min
-}
\ No newline at end of file
+    let offset = Decimal::from_u128(random_below_u128(rng, range)).ok_or_else(conversion_error)?;
+    offset.checked_add(low).ok_or_else(conversion_error)
+}
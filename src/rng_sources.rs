@@ -0,0 +1,81 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::rng_traits::GrandRng;
+
+/// Uses the randomness of the operating system (or `crypto.getRandomValues()` in the browser).
+/// This is the source used by `generate()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRng;
+
+impl GrandRng for OsRng {
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        getrandom::fill(buf).expect("getting random numbers should be possible");
+    }
+}
+
+/// Deterministic generator (xoshiro256**). Generators created with the same seed generate
+/// the same numbers on every platform, which allows reproducing tests or replaying sessions.
+///
+/// It's NOT cryptographically secure.
+///
+/// ```
+/// use grand::SeededRng;
+///
+/// let gex = grand::compile_raw("[0..10, 20..30|*2]").unwrap();
+/// let mut first = SeededRng::seed(42);
+/// let mut second = SeededRng::seed(42);
+/// for _ in 0..10 {
+///     assert_eq!(gex.generate_with(&mut first), gex.generate_with(&mut second));
+/// }
+///
+/// // The stream is the same on every platform
+/// use grand::GrandRng;
+/// let mut rng = SeededRng::seed(42);
+/// assert_eq!(rng.next_u64(), 1546998764402558742);
+/// assert_eq!(rng.next_u64(), 6990951692964543102);
+/// ```
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: [u64; 4],
+}
+
+#[wasm_bindgen]
+impl SeededRng {
+    /// Creates a generator from a seed. The state is expanded from the seed with SplitMix64
+    pub fn seed(seed: u64) -> SeededRng {
+        let mut splitmix = seed;
+        let mut state = [0u64; 4];
+        for value in &mut state {
+            splitmix = splitmix.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *value = z ^ (z >> 31);
+        }
+        SeededRng { state }
+    }
+}
+
+impl GrandRng for SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
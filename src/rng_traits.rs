@@ -2,115 +2,163 @@ use std::fmt::{Debug, Display};
 
 use rust_decimal::Decimal;
 
+use crate::rng_sources::OsRng;
+
+/// Source of random bytes used to generate numbers.
+///
+/// Numbers are built from these bytes in little-endian order, so the same bytes generate
+/// the same numbers on every platform (WASM included).
+/// `OsRng` uses the randomness of the operating system and `SeededRng` is a deterministic
+/// generator that can be used to reproduce results.
+pub trait GrandRng {
+    fn fill_bytes(&mut self, buf: &mut [u8]);
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+}
+
+/// Types that can be generated from random bytes.
+///
+/// Implementors only provide `random_with()` (before 0.3.0 they provided `random()`, see the CHANGELOG).
+/// Taking every byte from the given source keeps the values reproducible with `SeededRng`:
+///
+/// ```
+/// use grand::{GrandRng, Randomizable, SeededRng};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Coin(bool);
+///
+/// impl std::fmt::Display for Coin {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", if self.0 { "heads" } else { "tails" })
+///     }
+/// }
+///
+/// impl Randomizable for Coin {
+///     fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
+///         Coin(u8::random_with(rng) % 2 == 0)
+///     }
+/// }
+///
+/// let first = Coin::random_with(&mut SeededRng::seed(0x5EED));
+/// assert_eq!(first, Coin::random_with(&mut SeededRng::seed(0x5EED)));
+/// ```
 pub trait Randomizable: Display + Debug {
-    fn random() -> Self;
+    /// Generates a random value using the bytes of the given source
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self;
+
+    /// Generates a random value using the randomness of the operating system
+    fn random() -> Self where Self: Sized {
+        Self::random_with(&mut OsRng)
+    }
 }
 
 // BYTE
 impl Randomizable for u8 {
-    fn random() -> Self {
-        let mut number = [0u8];
-        getrandom::fill(&mut number).expect("getting random numbers should be possible");
-        number[0]
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
+        let mut buf = [0u8; 1];
+        rng.fill_bytes(&mut buf);
+        u8::from_le_bytes(buf)
     }
-    
 }
 impl Randomizable for i8 {
-    fn random() -> Self {
-        let mut buf = [0u8];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        i8::from_ne_bytes(buf)
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
+        let mut buf = [0u8; 1];
+        rng.fill_bytes(&mut buf);
+        i8::from_le_bytes(buf)
     }
 }
 // SHORT
 impl Randomizable for u16 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 2];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        u16::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        u16::from_le_bytes(buf)
     }
 }
 impl Randomizable for i16 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 2];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        i16::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        i16::from_le_bytes(buf)
     }
 }
 // INT
 impl Randomizable for u32 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 4];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        u32::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
     }
 }
 impl Randomizable for i32 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 4];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        i32::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        i32::from_le_bytes(buf)
     }
 }
 // LONG
 impl Randomizable for u64 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 8];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        u64::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
     }
 }
 impl Randomizable for i64 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 8];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        i64::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        i64::from_le_bytes(buf)
     }
 }
 // COMICALLY LARGE
 impl Randomizable for u128 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 16];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        u128::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        u128::from_le_bytes(buf)
     }
 }
 impl Randomizable for i128 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 16];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        i128::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        i128::from_le_bytes(buf)
     }
 }
 
 // USIZE
+// Always generated from 8 bytes so 32 bit platforms (like WASM) get the same numbers (truncated)
 impl Randomizable for usize {
-    fn random() -> Self {
-        let mut buf = [0u8; size_of::<usize>()];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        usize::from_ne_bytes(buf)
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
+        u64::random_with(rng) as usize
     }
 }
 
 // DECIMALS
 impl Randomizable for f32 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 4];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        f32::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        f32::from_le_bytes(buf)
     }
 }
 impl Randomizable for f64 {
-    fn random() -> Self {
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
         let mut buf = [0u8; 8];
-        getrandom::fill(&mut buf).expect("getting random numbers should be possible");
-        f64::from_ne_bytes(buf)
+        rng.fill_bytes(&mut buf);
+        f64::from_le_bytes(buf)
     }
 }
 
 impl Randomizable for Decimal {
-    fn random() -> Self {
-        let scale = u32::random() % Decimal::MAX_SCALE;
-        Decimal::from_parts(u32::random(), u32::random(), u32::random(), u8::random()%2==0, scale)
+    fn random_with<R: GrandRng + ?Sized>(rng: &mut R) -> Self {
+        let scale = u32::random_with(rng) % Decimal::MAX_SCALE;
+        Decimal::from_parts(u32::random_with(rng), u32::random_with(rng), u32::random_with(rng), u8::random_with(rng)%2==0, scale)
     }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+use grand::GenerateError;
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{bounds, generate};
+
+#[test]
+fn sum() {
+    assert_eq!(bounds("sum((1..6:int){3})"), (3.into(), 18.into()));
+    assert_eq!(bounds("sum([1..3, 5..8])"), (6.into(), 11.into()));
+    assert_eq!(bounds("sum((1..6){3}, 10)"), (13.into(), 28.into()));
+    // Negative numbers make the sum smaller the more there are
+    assert_eq!(bounds("sum((-1..1){2,4})"), ((-4).into(), 4.into()));
+    assert_eq!(bounds("sum((1..6){0,3})"), (0.into(), 18.into()));
+    generate("sum((1..6:int){3})", 1000);
+    generate("sum((-1..1){2,4})", 1000);
+}
+
+#[test]
+fn min_and_max() {
+    assert_eq!(bounds("min(0..10, 5..20)"), (0.into(), 10.into()));
+    assert_eq!(bounds("max(0..10, 5..20)"), (5.into(), 20.into()));
+    // Advantage is better on average than a single roll
+    let advantage: Decimal = generate("max((1..20:int){2})", 2000).iter().sum();
+    let single: Decimal = generate("1..20:int", 2000).iter().sum();
+    assert!(advantage > single);
+    generate("min(0..10, 5..20)", 1000);
+}
+
+#[test]
+fn avg_and_kth() {
+    assert_eq!(bounds("avg((1..6){4})"), (1.into(), 6.into()));
+    assert!(generate("kth(2, [1, 5, 3])", 100).iter().all(|number| *number == 3.into()));
+    assert!(generate("kth(1, [4, 2, 3])", 100).iter().all(|number| *number == 2.into()));
+    assert!(generate("avg([2, 4])", 100).iter().all(|number| *number == 3.into()));
+    generate("kth(2, (1..6:int){5})", 1000);
+}
+
+#[test]
+fn aggregates_in_expressions() {
+    assert_eq!(bounds("max(1, 2)*2"), (4.into(), 4.into()));
+    assert_eq!(bounds("0..max(1, 5)"), (0.into(), 5.into()));
+    // Weighted selections are a single number
+    assert_eq!(bounds("max([1:2, 50], 3)"), (3.into(), 50.into()));
+    // Nested aggregates with quantifiers
+    assert_eq!(bounds("sum(max((1..6){2}){2})"), (2.into(), 12.into()));
+    generate("sum(max((1..6){2}){2})", 1000);
+}
+
+#[test]
+fn empty_lists() {
+    let gex = grand::compile_raw("kth(3, [1, 2])").unwrap();
+    assert!(matches!(gex.try_generate(), Err(GenerateError::NotEnoughValues(3, 2))));
+    let gex = grand::compile_raw("max((0..1){0})").unwrap();
+    assert!(matches!(gex.try_generate(), Err(GenerateError::NotEnoughValues(1, 0))));
+    assert_eq!(grand::compile_raw("sum((0..1){0})").unwrap().try_generate().unwrap(), Decimal::ZERO);
+}
+
+#[test]
+fn invalid_calls() {
+    for expression in ["foo(1)", "sum()", "kth([1, 2])", "kth(0, [1, 2])", "kth(0.5, [1, 2])", "kth(1..2, [1, 2])", "max((1..6){3}{2})", "max(1, 2"] {
+        assert!(grand::compile_raw(expression).is_err(), "{expression}");
+    }
+    // Words that aren't followed by a parenthesis are ignored outside of strict mode
+    assert!(grand::compile_raw("max 1..2").is_ok());
+}
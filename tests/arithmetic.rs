@@ -0,0 +1,68 @@
+use grand::{GenerateError, SeededRng};
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{bounds, generate};
+
+#[test]
+fn precedence() {
+    assert_eq!(generate("2+3*4", 1), vec![14.into()]);
+    assert_eq!(generate("(2+3)*4", 1), vec![20.into()]);
+    assert_eq!(generate("10-4-3", 1), vec![3.into()]);
+    assert_eq!(generate("20/2/5", 1), vec![2.into()]);
+    assert_eq!(generate("7%4*2", 1), vec![6.into()]);
+    assert_eq!(generate("-2*-3", 1), vec![6.into()]);
+    assert_eq!(generate("--5+-1", 1), vec![4.into()]);
+}
+
+#[test]
+fn bounds_propagate() {
+    assert_eq!(bounds("(1..6)+(1..6)"), (2.into(), 12.into()));
+    assert_eq!(bounds("(1..6)-(1..6)"), ((-5).into(), 5.into()));
+    assert_eq!(bounds("(-2..3)*(-4..5)"), ((-12).into(), 15.into()));
+    assert_eq!(bounds("(2..4)/(1..2)"), (1.into(), 4.into()));
+    assert_eq!(bounds("(0..100)%7"), (0.into(), 7.into()));
+    assert_eq!(bounds("-(0..10)"), ((-10).into(), 0.into()));
+    // Ranges go before operators
+    assert_eq!(bounds("0..10*2"), (0.into(), 20.into()));
+}
+
+#[test]
+fn results_stay_within_bounds() {
+    for expression in ["(1..6:int)+(1..6:int)", "(0..10)*0.5+3", "-(1..3)*(2..4)", "(0..100)%7", "0..10|*2+100"] {
+        let (min, max) = bounds(expression);
+        assert!(generate(expression, 1000).iter().all(|number| *number >= min && *number <= max), "{expression}");
+    }
+}
+
+#[test]
+fn constrained_operands() {
+    let numbers = generate("0..10|*2+1", 1000);
+    assert!(numbers.iter().all(|number| number % Decimal::TWO == Decimal::ONE));
+    // Bounds made with operations can be precalculated too
+    let numbers = generate("(2-2)..(5*2)|*2|!*4", 1000);
+    assert!(numbers.iter().all(|number| [2, 6, 10].map(Decimal::from).contains(number)));
+}
+
+#[test]
+fn division_by_zero() {
+    assert!(grand::compile_raw("10/0").is_err());
+    assert!(grand::compile_raw("10%(3-3)").is_err());
+
+    assert!(grand::compile_raw("10/[0, 0]").is_err());
+
+    // Only known when generating
+    let gex = grand::compile_raw("10/[0, 1]").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let results: Vec<Result<Decimal, GenerateError>> = (0..100).map(|_| gex.try_generate_with(&mut rng)).collect();
+    assert!(results.iter().any(|result| matches!(result, Err(GenerateError::DivisionByZero(_)))));
+    assert!(results.iter().any(|result| matches!(result, Ok(number) if *number == 10.into())));
+}
+
+#[test]
+fn missing_operands() {
+    for expression in ["5+", "5*", "(5-)", "[1, 2/]", "5+*2"] {
+        assert!(grand::compile_raw(expression).is_err(), "{expression}");
+    }
+}
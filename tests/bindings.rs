@@ -0,0 +1,51 @@
+use grand::{CompilerError, CompilerWarning, DiagnosticKind};
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::generate;
+
+#[test]
+fn sampled_once_per_generation() {
+    assert!(generate("$a = 0..1000; $a - $a", 100).iter().all(|number| number.is_zero()));
+    assert!(generate("$a = 0..1000; [$a, $a] - $a", 100).iter().all(|number| number.is_zero()));
+
+    // Every generation gets a new value
+    let mut numbers = generate("$a = 0..1000:int; $a", 100);
+    numbers.dedup();
+    assert!(numbers.len() > 1);
+}
+
+#[test]
+fn bindings_as_bounds() {
+    let numbers = generate("$a = 0..10; ($a..($a+5)) - $a", 1000);
+    assert!(numbers.iter().all(|number| *number >= Decimal::ZERO && *number <= 5.into()));
+
+    let gex = grand::compile_raw("$a = 0..10; $a..($a+5)").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), 15.into()));
+}
+
+#[test]
+fn definitions_use_previous_bindings() {
+    let numbers = generate("$a = 1..10:int; $b = $a*2; $b % 2 + $b - 2*$a", 100);
+    assert!(numbers.iter().all(|number| number.is_zero()));
+}
+
+#[test]
+fn unknown_and_duplicate_bindings() {
+    let errors = |expression: &str| -> Vec<DiagnosticKind> {
+        grand::compile_raw(expression).unwrap_err().iter().map(|diagnostic| diagnostic.kind().clone()).collect()
+    };
+    assert!(matches!(errors("$a..10")[0], DiagnosticKind::Error(CompilerError::UnknownBinding(_, 0, 1))));
+    assert!(matches!(errors("$a = $a + 1; $a")[0], DiagnosticKind::Error(CompilerError::UnknownBinding(_, 0, 6))));
+    assert!(matches!(errors("$a = 1; $a = 2; $a")[0], DiagnosticKind::Error(CompilerError::DuplicateBinding(_, 0, 9))));
+    assert!(grand::compile_raw("$a = 0..10;").is_err());
+    assert!(grand::compile_raw("$a = 0..10 $a").is_err());
+}
+
+#[test]
+fn unused_bindings_are_warnings() {
+    let warnings = grand::compile("$a = 1; $b = 2; $b").unwrap().warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(warnings[0].kind(), DiagnosticKind::Warning(CompilerWarning::UnusedBinding(name, 0, 1)) if name == "$a"));
+}
@@ -0,0 +1,110 @@
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+use grand::{GenerateError, SeededRng};
+use rust_decimal::Decimal;
+
+fn is_prime(number: &Decimal) -> bool {
+    let number = number.mantissa();
+    number >= 2 && (2..).take_while(|divisor| divisor * divisor <= number).all(|divisor| number % divisor != 0)
+}
+
+fn digit_sum(number: &Decimal) -> u32 {
+    number.to_string().chars().filter_map(|digit| digit.to_digit(10)).sum()
+}
+
+#[test]
+fn filter_rerolls_until_accepted() {
+    let gex = grand::compile_raw("2..1000:int").unwrap().filter(|number| !is_prime(number));
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..1000 {
+        let number = gex.generate_with(&mut rng);
+        assert!(!is_prime(&number) && number >= 2.into() && number <= 1000.into(), "{number}");
+    }
+    let gex = grand::compile_raw("0..1000:int").unwrap().filter(|number| digit_sum(number) == 7);
+    for _ in 0..100 {
+        assert_eq!(digit_sum(&gex.generate_with(&mut rng)), 7);
+    }
+}
+
+#[test]
+fn filter_rerolls_bindings() {
+    // Every attempt generates the bindings again, the body uses the values of the same attempt
+    let gex = grand::compile_raw("$a = 1..100:int; $a").unwrap().filter(|number| *number > 90.into());
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..200 {
+        assert!(gex.try_generate_with(&mut rng).unwrap() > 90.into());
+    }
+    let gex = grand::compile_raw("$a = 1..100:int; $b = $a*2; $b - $a").unwrap().filter(|number| *number > 90.into());
+    for _ in 0..200 {
+        assert!(gex.try_generate_with(&mut rng).unwrap() > 90.into());
+    }
+}
+
+#[test]
+fn filter_keeps_the_bounds() {
+    let gex = grand::compile_raw("0..10").unwrap().filter(|number| *number > 5.into());
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), 10.into()));
+}
+
+#[test]
+fn filter_reports_exhaustion() {
+    // Nothing satisfies the predicate, the unfiltered numbers are never returned
+    let mut gex = grand::compile_raw("0..10").unwrap().filter(|number| *number > 100.into());
+    assert!(matches!(gex.try_generate(), Err(GenerateError::RerollsExhausted(1000))));
+    gex.set_max_rerolls(5);
+    assert!(matches!(gex.try_generate(), Err(GenerateError::RerollsExhausted(5))));
+
+    // The maximum amount of rerolls of the compiled expression is kept
+    let options = grand::CompileOptions { max_rerolls: 3, ..Default::default() };
+    let gex = grand::compile_raw_with("0..1000|!*7", &options).unwrap().filter(|_| false);
+    assert!(matches!(gex.try_generate(), Err(GenerateError::RerollsExhausted(3))));
+
+    // Expressions without constraints use the options too
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let gex = grand::compile_raw_with("1..100:int", &options).unwrap().filter(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        false
+    });
+    assert!(matches!(gex.try_generate(), Err(GenerateError::RerollsExhausted(3))));
+    assert_eq!(attempts.load(Ordering::Relaxed), 4);
+}
+
+#[test]
+fn filter_keeps_constraints() {
+    let gex = grand::compile_raw("0..1000|*3|!*2").unwrap().filter(|number| *number > 500.into());
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..1000 {
+        let number = gex.generate_with(&mut rng);
+        assert!(number > 500.into() && number % Decimal::from(3) == Decimal::ZERO && number % Decimal::TWO != Decimal::ZERO);
+    }
+}
+
+#[test]
+fn map_transforms_numbers() {
+    let gex = grand::compile_raw("1..6:int").unwrap().map(|number| number * Decimal::TEN);
+    assert_eq!((gex.min_number(), gex.max_number()), (Decimal::MIN, Decimal::MAX));
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        let number = gex.generate_with(&mut rng);
+        assert!(number >= 10.into() && number <= 60.into() && number % Decimal::TEN == Decimal::ZERO);
+    }
+}
+
+#[test]
+fn combinators_can_be_chained() {
+    // Filters after a map see the mapped numbers
+    let gex = grand::compile_raw("1..6:int").unwrap()
+        .map(|number| number * Decimal::TWO)
+        .filter(|number| *number > 6.into());
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        assert!([8, 10, 12].map(Decimal::from).contains(&gex.generate_with(&mut rng)));
+    }
+}
+
+#[test]
+fn combinators_need_single_numbers() {
+    let gex = grand::compile_raw("(1..6){3}").unwrap().map(|number| number);
+    assert!(matches!(gex.try_generate(), Err(GenerateError::MultipleValues)));
+}
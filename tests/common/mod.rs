@@ -0,0 +1,50 @@
+// Helpers shared by the integration tests. Not every test uses all of them
+#![allow(dead_code)]
+
+use grand::SeededRng;
+use rust_decimal::Decimal;
+
+// Every number generated is within the bounds of the expression. Seeded, so the results never change. Returns the numbers
+pub fn generate(expression: &str, samples: usize) -> Vec<Decimal> {
+    let gex = grand::compile_raw(expression).unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    (0..samples).map(|_| {
+        let number = gex.generate_with(&mut rng);
+        assert!(number >= gex.min_number() && number <= gex.max_number(), "{expression}: {number}");
+        number
+    }).collect()
+}
+
+pub fn bounds(expression: &str) -> (Decimal, Decimal) {
+    let gex = grand::compile_raw(expression).unwrap();
+    (gex.min_number(), gex.max_number())
+}
+
+// Every value generated by the expression is counted. Seeded, so the results never change
+pub fn count(expression: &str, samples: usize) -> Vec<(Decimal, usize)> {
+    let gex = grand::compile_raw(expression).unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let mut counts: Vec<(Decimal, usize)> = Vec::new();
+    for _ in 0..samples {
+        let number = gex.generate_with(&mut rng);
+        match counts.iter_mut().find(|(value, _)| *value == number) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((number, 1)),
+        }
+    }
+    counts.sort();
+    counts
+}
+
+// Pearson's chi-squared statistic for the given probabilities (same order as the counts)
+pub fn chi_squared(counts: &[(Decimal, usize)], probabilities: &[f64], samples: usize) -> f64 {
+    counts.iter().zip(probabilities).map(|((_, n), probability)| {
+        let expected = samples as f64 * probability;
+        (*n as f64 - expected).powi(2) / expected
+    }).sum()
+}
+
+// Probabilities of a uniform distribution over this amount of values
+pub fn uniform(values: usize) -> Vec<f64> {
+    vec![1.0 / values as f64; values]
+}
@@ -0,0 +1,66 @@
+use grand::{CompilerError, DiagnosticKind, Severity};
+
+#[test]
+fn every_error_is_reported() {
+    let diagnostics = grand::compile_raw("1..2 + @").unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.has_errors());
+    let errors: Vec<_> = diagnostics.iter().map(|diagnostic| (diagnostic.kind(), diagnostic.start(), diagnostic.end())).collect();
+    assert!(matches!(errors[0], (DiagnosticKind::Error(CompilerError::MissingOperand(_, 0, 6)), 5, 8)));
+    assert!(matches!(errors[1], (DiagnosticKind::Error(CompilerError::InvalidPrecision(_, 0, 8)), 7, 8)));
+
+    // Errors in different lines, in the order they are found
+    let diagnostics = grand::compile_raw("(1..2\n+ 3..4|*").unwrap_err();
+    let errors: Vec<_> = diagnostics.iter().map(|diagnostic| (diagnostic.kind(), diagnostic.start(), diagnostic.end())).collect();
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(errors[0], (DiagnosticKind::Error(CompilerError::DanglingConstraint(1, 7)), 12, 14)));
+    assert!(matches!(errors[1], (DiagnosticKind::Error(CompilerError::UnbalancedDelimiter(_, 0, 1)), 0, 1)));
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.severity() == Severity::Error));
+}
+
+#[test]
+fn rendered_diagnostics() {
+    let diagnostics = grand::compile_raw("1..2 + @").unwrap_err();
+    assert_eq!(diagnostics.source(), "1..2 + @");
+    assert_eq!(diagnostics.to_string(), [
+        "error: Missing operand in line 0, column 6. Plus must be followed by a number, selection or sub-expression",
+        " 0 | 1..2 + @",
+        "   |      ^^^",
+        "error: Invalid precision in line 0, column 8. \"@\" is not a valid precision, expected \":int\" or \"@\" followed by an amount of decimals (0 to 28)",
+        " 0 | 1..2 + @",
+        "   |        ^",
+    ].join("\n"));
+
+    // Only the line of the problem is shown
+    let diagnostics = grand::compile_raw("(1..2\n+ 3..4|*").unwrap_err();
+    let diagnostic = diagnostics.iter().next().unwrap();
+    assert_eq!(diagnostic.render(diagnostics.source()), [
+        "error: Dangling constraint in line 1, column 7. Expected a constraint type followed by its values",
+        " 1 | + 3..4|*",
+        "   |       ^^",
+    ].join("\n"));
+}
+
+#[test]
+fn unexpected_tokens_before_closing_delimiters() {
+    // The delimiters are balanced, only the token before the closing one is reported
+    for (source, expected, start, end) in [
+        ("[1:5 2]", "RBrack", 5, 6),
+        ("max(1:5)", "RParen", 5, 7),
+        ("(1..6){3:4}", "RBrace", 8, 10),
+        ("[1:1e3,2]", "RBrack", 5, 6),
+    ] {
+        let diagnostics = grand::compile_raw(source).unwrap_err();
+        assert_eq!(diagnostics.len(), 1, "{source}");
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert!(matches!(diagnostic.kind(), DiagnosticKind::Error(CompilerError::UnexpectedToken(_, _, 0, _))), "{source}");
+        assert!(diagnostic.message().contains(&format!("Expected one of [{expected}]")), "{source}");
+        assert_eq!((diagnostic.start(), diagnostic.end()), (start, end), "{source}");
+    }
+
+    // Without a closing delimiter, the opening one is reported
+    let diagnostics = grand::compile_raw("max(1, 2").unwrap_err();
+    let errors: Vec<_> = diagnostics.iter().map(|diagnostic| (diagnostic.kind(), diagnostic.start(), diagnostic.end())).collect();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], (DiagnosticKind::Error(CompilerError::UnbalancedDelimiter(_, 0, 4)), 3, 4)));
+}
@@ -0,0 +1,102 @@
+use grand::{CompilerError, DiagnosticKind, SeededRng};
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{chi_squared, count};
+
+// Probability of every sum of 3d6, from 3 to 18
+fn three_dice() -> Vec<f64> {
+    let mut ways = [0u32; 16];
+    for a in 1..=6 {
+        for b in 1..=6 {
+            for c in 1..=6 {
+                ways[a + b + c - 3] += 1;
+            }
+        }
+    }
+    ways.iter().map(|ways| *ways as f64 / 216.0).collect()
+}
+
+#[test]
+fn sum_of_dice() {
+    let samples = 100000;
+    let counts = count("3d6", samples);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, (3..=18).map(Decimal::from).collect::<Vec<Decimal>>());
+    // 15 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &three_dice(), samples) < 37.70);
+}
+
+#[test]
+fn rolled_dice_follow_the_same_distribution() {
+    // Too many dice to be precalculated: every die is rolled
+    let gex = grand::compile_raw("200d6").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (200.into(), 1200.into()));
+    let mut rng = SeededRng::seed(0x5EED);
+    let samples = 2000;
+    let mean: f64 = (0..samples).map(|_| f64::try_from(gex.generate_with(&mut rng)).unwrap()).sum::<f64>() / samples as f64;
+    // Standard deviation of the mean is about 0.54
+    assert!((mean - 700.0).abs() < 3.0, "{mean}");
+}
+
+#[test]
+fn keep_and_drop() {
+    let samples = 100000;
+    // Keeping the 3 highest of 4d6, by enumerating every roll
+    let mut ways = [0u32; 16];
+    for roll in 0..1296 {
+        let mut faces = [roll % 6 + 1, roll / 6 % 6 + 1, roll / 36 % 6 + 1, roll / 216 + 1];
+        faces.sort();
+        ways[faces[1..].iter().sum::<usize>() - 3] += 1;
+    }
+    let probabilities: Vec<f64> = ways.iter().map(|ways| *ways as f64 / 1296.0).collect();
+    for expression in ["4d6kh3", "4d6dl1", "4d6dl"] {
+        let counts = count(expression, samples);
+        assert_eq!(counts.len(), 16);
+        // 15 degrees of freedom, p = 0.001
+        assert!(chi_squared(&counts, &probabilities, samples) < 37.70, "{expression}");
+    }
+
+    let lowest = count("2d20kl", 1000);
+    let highest = count("2d20kh", 1000);
+    assert!(lowest.iter().map(|(value, n)| *value * Decimal::from(*n)).sum::<Decimal>() < highest.iter().map(|(value, n)| *value * Decimal::from(*n)).sum::<Decimal>());
+}
+
+#[test]
+fn exploding_dice() {
+    let gex = grand::compile_raw("1d6!").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 606.into()));
+    // A 6 is always rolled again, so no result is a multiple of 6
+    let counts = count("1d6!", 10000);
+    assert!(counts.iter().all(|(value, _)| !(*value % Decimal::from(6)).is_zero()));
+    assert!(counts.iter().any(|(value, _)| *value > Decimal::from(6)));
+    assert!(grand::compile_raw("5d10!kh2").is_ok());
+    assert!(grand::compile_raw("5d10kh2!").is_ok());
+}
+
+#[test]
+fn dice_in_expressions() {
+    let gex = grand::compile_raw("2d20kh+5").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (6.into(), 25.into()));
+    let gex = grand::compile_raw("[d4, d6]|uniform").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 6.into()));
+}
+
+#[test]
+fn invalid_dice() {
+    for expression in ["0d6", "3d0", "4d6kh5", "4d6kh0", "4d6dl4", "3d1!", "3d6!!", "99999999999d6"] {
+        assert!(grand::compile_raw(expression).is_err(), "{expression}");
+    }
+}
+
+#[test]
+fn too_many_dice() {
+    for expression in ["4294967295d6", "4294967295d6kh1", "100001d6", "3d100001", "d4294967295"] {
+        let diagnostics = grand::compile_raw(expression).unwrap_err();
+        assert!(matches!(diagnostics.iter().next().unwrap().kind(), DiagnosticKind::Error(CompilerError::InvalidDice(..))), "{expression}");
+    }
+    let max = grand::MAX_DICE;
+    let gex = grand::compile_raw(&format!("{max}d{max}")).unwrap();
+    assert_eq!(gex.max_number(), Decimal::from(max) * Decimal::from(max));
+}
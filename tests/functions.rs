@@ -0,0 +1,85 @@
+use grand::{GenerateError, SeededRng};
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{bounds, generate};
+
+#[test]
+fn abs_floor_and_ceil() {
+    assert_eq!(bounds("abs(-5..3)"), (0.into(), 5.into()));
+    assert_eq!(bounds("abs(-5..-2)"), (2.into(), 5.into()));
+    assert_eq!(bounds("floor(-1.5..1.5)"), ((-2).into(), 1.into()));
+    assert_eq!(bounds("ceil(-1.5..1.5)"), ((-1).into(), 2.into()));
+    assert!(generate("floor(0..10)", 1000).iter().all(|number| number.fract().is_zero()));
+    generate("abs(-5..3)", 1000);
+    generate("ceil(-1.5..1.5)", 1000);
+}
+
+#[test]
+fn round() {
+    assert!(generate("round(0..10, 2)", 1000).iter().all(|number| number.round_dp(2) == *number));
+    assert!(generate("round(0..10)", 1000).iter().all(|number| number.fract().is_zero()));
+    // Half to even
+    assert_eq!(grand::compile_raw("round(2.5)").unwrap().generate(), 2.into());
+    assert_eq!(grand::compile_raw("round(0.125, 2)").unwrap().generate(), Decimal::new(12, 2));
+    // The amount of decimals is generated as an integer
+    generate("round(0..10, 0..3)", 1000);
+    generate("round(1.4..1.6)", 1000);
+}
+
+#[test]
+fn clamp() {
+    assert_eq!(bounds("clamp(-50..150, 0, 100)"), (0.into(), 100.into()));
+    assert_eq!(bounds("clamp(0..10, 2..3, 20)"), (2.into(), 10.into()));
+    let numbers = generate("clamp(-50..150, 0, 100)", 1000);
+    assert!(numbers.contains(&0.into()) && numbers.contains(&100.into()));
+    // The minimum is bigger than the maximum sometimes
+    let gex = grand::compile_raw("clamp(1, 0..10, 5)").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let errors = (0..100).filter(|_| matches!(gex.try_generate_with(&mut rng), Err(GenerateError::InvalidArgument(_, _)))).count();
+    assert!(errors > 0 && errors < 100);
+}
+
+#[test]
+fn pow() {
+    assert_eq!(bounds("pow(2, 0..10:int)"), (1.into(), 1024.into()));
+    assert_eq!(bounds("pow(-2..2, 3)"), ((-8).into(), 8.into()));
+    assert_eq!(bounds("pow(-2..2, 2)"), (0.into(), 4.into()));
+    assert_eq!(grand::compile_raw("pow(3, 4)").unwrap().generate(), 81.into());
+    generate("pow(2, 0..10)", 1000);
+    generate("pow(-2..2, 3)", 1000);
+    generate("pow(0..4, 0.5)", 1000);
+    // Roots of negative numbers
+    let gex = grand::compile_raw("pow(-1, 0.5)").unwrap();
+    assert!(matches!(gex.try_generate(), Err(GenerateError::InvalidArgument(name, _)) if name == "pow"));
+}
+
+#[test]
+fn log() {
+    assert_eq!(bounds("log(1..10)").0, Decimal::ZERO);
+    assert_eq!(grand::compile_raw("log(100, 10)").unwrap().generate().round_dp(10), 2.into());
+    generate("log(1..100)", 1000);
+    generate("log(1..1000, 10)", 1000);
+    generate("log(1..1000, 2..10)", 1000);
+    // 0 is generated sometimes
+    let gex = grand::compile_raw("log([0, 1])").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let errors = (0..100).filter(|_| matches!(gex.try_generate_with(&mut rng), Err(GenerateError::InvalidArgument(_, _)))).count();
+    assert!(errors > 0 && errors < 100);
+}
+
+#[test]
+fn functions_in_expressions() {
+    assert_eq!(bounds("abs(-3..3)*2"), (0.into(), 6.into()));
+    assert_eq!(bounds("max(abs(-3..3), 1)"), (1.into(), 3.into()));
+    assert_eq!(bounds("round(pow(2, 0.5), 3)"), (Decimal::new(1414, 3), Decimal::new(1415, 3)));
+    generate("0..abs(-5..-1)", 1000);
+}
+
+#[test]
+fn invalid_calls() {
+    for expression in ["abs()", "abs(1, 2)", "clamp(1, 2)", "pow(2)", "log(1, 2, 3)", "round(1..2, -1)", "round(1, 29)", "clamp(1, 5, 2)", "log(-5..0)", "log(1..10, 1)", "log(1..10, -2)", "median(1, 2)", "abs((1..6){3})"] {
+        assert!(grand::compile_raw(expression).is_err(), "{expression}");
+    }
+}
@@ -0,0 +1,74 @@
+use grand::{CompileOptions, CompilerError, DiagnosticKind, GenerateError, ParamDeclaration, Params};
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::generate;
+
+fn decimal(number: &str) -> Decimal {
+    number.parse().unwrap()
+}
+
+#[test]
+fn tiny_open_range_stays_inside() {
+    let max = decimal("0.0005");
+    for number in generate("0,,0.0005", 10000) {
+        assert!(number > Decimal::ZERO && number < max);
+    }
+}
+
+#[test]
+fn open_range_excludes_only_the_endpoints() {
+    // Only 4 numbers can be represented between the bounds at the sampling scale
+    let mut numbers = generate("0,,0.0000000000000000000000000005", 1000);
+    numbers.sort();
+    numbers.dedup();
+    let expected: Vec<Decimal> = ["0.0000000000000000000000000001", "0.0000000000000000000000000002", "0.0000000000000000000000000003", "0.0000000000000000000000000004"]
+        .map(decimal)
+        .to_vec();
+    assert_eq!(numbers, expected);
+}
+
+#[test]
+fn half_open_ranges() {
+    let mut numbers = generate("0,.0.0000000000000000000000000002", 1000);
+    numbers.sort();
+    numbers.dedup();
+    assert_eq!(numbers, ["0.0000000000000000000000000001", "0.0000000000000000000000000002"].map(decimal).to_vec());
+
+    let mut numbers = generate("0.,0.0000000000000000000000000002", 1000);
+    numbers.sort();
+    numbers.dedup();
+    assert_eq!(numbers, ["0", "0.0000000000000000000000000001"].map(decimal).to_vec());
+}
+
+#[test]
+fn open_range_with_multiples() {
+    let mut numbers = generate("0,,0.001|*0.0005", 100);
+    numbers.dedup();
+    assert_eq!(numbers, vec![decimal("0.0005")]);
+
+    let mut numbers = generate("0,.1|*0.5", 1000);
+    numbers.sort();
+    numbers.dedup();
+    assert_eq!(numbers, ["0.5", "1"].map(decimal).to_vec());
+}
+
+#[test]
+fn empty_open_range() {
+    // Ranges that are always empty are reported while compiling
+    for expression in ["1,,1", "1,.1", "1.,1", "0,,0.5|*0.5", "0,,10|*10", "5..3", "5,,3", "(1+1)..1", "6..9|*10", "0,,1:int", "0.2..0.8:int", "0.01..0.04@1"] {
+        let diagnostics = grand::compile_raw(expression).unwrap_err();
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert!(matches!(diagnostic.kind(), DiagnosticKind::Error(CompilerError::EmptySupport(0, 1))), "{expression}");
+        assert_eq!((diagnostic.start(), diagnostic.end()), (0, expression.len()), "{expression}");
+    }
+    for expression in ["1..1", "0..10|*10", "0,.10|*10", "-5..-3", "0..(0..1)", "0,,2:int", "0.01..0.1@1"] {
+        assert!(grand::compile_raw(expression).is_ok(), "{expression}");
+    }
+
+    // The rest are reported while generating
+    let options = CompileOptions { params: vec![ParamDeclaration::new("x")], ..Default::default() };
+    let gex = grand::compile_raw_with("{x},,1", &options).unwrap();
+    assert!(matches!(gex.try_generate_with_params(&Params::new().with("x", 1)), Err(GenerateError::EmptyRange(_, _))));
+}
@@ -0,0 +1,67 @@
+use grand::{CompileOptions, CompilerError, DiagnosticKind, GenerateError, ParamDeclaration, Params, SeededRng};
+use rust_decimal::Decimal;
+
+fn options() -> CompileOptions {
+    CompileOptions {
+        params: vec![ParamDeclaration::new("level"), ParamDeclaration::bounded("price", 1.into(), 100.into())],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn same_expression_different_inputs() {
+    let gex = grand::compile_raw_with("{level}*10 + {price}", &options()).unwrap();
+    assert_eq!(gex.generate_with_params(&Params::new().with("level", 1).with("price", 5)), 15.into());
+    assert_eq!(gex.generate_with_params(&Params::new().with("level", 3).with("price", 5)), 35.into());
+}
+
+#[test]
+fn params_as_bounds() {
+    let gex = grand::compile_raw_with("{level}..{level}+5", &options()).unwrap();
+    let params = Params::new().with("level", 10);
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        let number = gex.try_generate_with_rng_and_params(&mut rng, &params).unwrap();
+        assert!(number >= 10.into() && number <= 15.into());
+    }
+}
+
+#[test]
+fn declared_bounds_propagate() {
+    let gex = grand::compile_raw_with("{price}*2", &options()).unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (2.into(), 200.into()));
+
+    // Bounded parameters can be precalculated, unbounded ones use rerolls
+    let gex = grand::compile_raw_with("0..{price}|*2|!*3", &options()).unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (2.into(), 100.into()));
+    assert_eq!(grand::compile_with("0..{level}|*2|!*3", &options()).unwrap().warnings().len(), 1);
+}
+
+#[test]
+fn unknown_params_are_compile_errors() {
+    let diagnostics = grand::compile_raw_with("{level} + {nope}", &options()).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(diagnostics.iter().next().unwrap().kind(), DiagnosticKind::Error(CompilerError::UnknownParam(name, 0, 11)) if name == "nope"));
+    assert!(grand::compile_raw("{level}").is_err());
+}
+
+#[test]
+fn missing_params_are_runtime_errors() {
+    let gex = grand::compile_raw_with("{level} + {price}", &options()).unwrap();
+    let result = gex.try_generate_with_params(&Params::new().with("level", 1));
+    assert!(matches!(result, Err(GenerateError::MissingParam(name)) if name == "price"));
+    assert!(matches!(gex.try_generate(), Err(GenerateError::MissingParam(_))));
+
+    let result = gex.try_generate_with_params(&Params::new().with("level", 1).with("price", Decimal::new(25, 1)));
+    assert_eq!(result.unwrap(), Decimal::new(35, 1));
+}
+
+#[test]
+fn only_the_chosen_entry_needs_its_params() {
+    // The entry with the parameter is never chosen, so the parameter doesn't have to be supplied
+    let gex = grand::compile_raw_with("[1:1, {level}:0]", &options()).unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        assert_eq!(gex.try_generate_with(&mut rng).unwrap(), 1.into());
+    }
+}
@@ -0,0 +1,93 @@
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::generate;
+
+#[test]
+fn narrow_range_keeps_its_precision() {
+    let max: Decimal = "0.000000000000001".parse().unwrap();
+    let numbers = generate("0..0.000000000000001", 1000);
+    assert!(numbers.iter().all(|number| *number >= Decimal::ZERO && *number <= max));
+    // f64 based generation only had a handful of possible values in this range
+    let mut distinct = numbers.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert!(distinct.len() > 990);
+}
+
+#[test]
+fn wide_range_keeps_its_precision() {
+    let min: Decimal = "100000000000000000000".parse().unwrap();
+    let max = min + Decimal::ONE;
+    let numbers = generate("100000000000000000000..100000000000000000001", 1000);
+    assert!(numbers.iter().all(|number| *number >= min && *number <= max));
+    assert!(numbers.iter().any(|number| !number.fract().is_zero()));
+}
+
+#[test]
+fn multiples_stay_inside_the_range() {
+    let numbers = generate("1..10|*2", 1000);
+    assert!(numbers.iter().all(|number| *number >= 2.into() && *number <= 10.into()));
+    assert!(numbers.iter().all(|number| (*number % Decimal::TWO).is_zero()));
+    assert!(numbers.contains(&10.into()));
+
+    let numbers = generate("0,,1|*0.25", 1000);
+    assert!(numbers.iter().all(|number| ["0.25", "0.5", "0.75"].contains(&number.normalize().to_string().as_str())));
+}
+
+#[test]
+fn integer_ranges() {
+    let numbers = generate("-3..3:int", 1000);
+    for value in -3..=3 {
+        assert!(numbers.contains(&value.into()));
+    }
+    assert!(numbers.iter().all(|number| number.scale() == 0 && *number >= (-3).into() && *number <= 3.into()));
+    assert_eq!(numbers, generate("-3..3@0", 1000));
+}
+
+#[test]
+fn precision_has_a_predictable_scale() {
+    let numbers = generate("0..1@3", 1000);
+    assert!(numbers.iter().all(|number| number.scale() == 3 && *number >= Decimal::ZERO && *number <= Decimal::ONE));
+
+    // Precalculated ranges and multiples get the trailing zeros too
+    assert!(generate("0..10|*2|!*3@2", 100).iter().all(|number| number.scale() == 2));
+    assert!(generate("0..10|*2@1", 100).iter().all(|number| number.scale() == 1));
+}
+
+#[test]
+fn precision_propagates_to_selections() {
+    let numbers = generate("[(0..1), [2..3, 4.56789]]@2", 1000);
+    assert!(numbers.iter().all(|number| number.scale() == 2));
+    assert!(numbers.contains(&"4.57".parse().unwrap()));
+
+    // Entries with their own precision are generated with it and rounded by the selection
+    let numbers = generate("[0..1@1, 5..6]:int", 1000);
+    assert!(numbers.iter().all(|number| number.scale() == 0));
+}
+
+#[test]
+fn bounds_are_rounded() {
+    // Rounded numbers can be beyond the bounds of the expression before rounding
+    let gex = grand::compile_raw("[(0..1), [2..3, 4.56789]]@2").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), "4.57".parse().unwrap()));
+    let gex = grand::compile_raw("(1.2..3.7) + 0@0").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 4.into()));
+
+    // Ranges with constant bounds only generate the numbers with the precision between their bounds
+    let gex = grand::compile_raw("(1.2..3.7)@0").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (2.into(), 3.into()));
+    assert!(common::generate("(1.2..3.7)@0", 100).iter().all(|number| *number == 2.into() || *number == 3.into()));
+    let gex = grand::compile_raw("0,,2:int").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 1.into()));
+    let gex = grand::compile_raw("0.01..0.15@1").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), ("0.1".parse().unwrap(), "0.1".parse().unwrap()));
+}
+
+#[test]
+fn invalid_precision() {
+    assert!(grand::compile_raw("0..1@29").is_err());
+    assert!(grand::compile_raw("0..1:float").is_err());
+    assert!(grand::compile_raw("@2").is_err());
+}
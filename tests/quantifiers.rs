@@ -0,0 +1,78 @@
+use grand::{CompileOptions, GenerateError, ParamDeclaration, Params, SeededRng};
+use rust_decimal::Decimal;
+
+fn generate_many(expression: &str) -> Vec<Decimal> {
+    let gex = grand::compile_raw(expression).unwrap();
+    gex.generate_many_with(&mut SeededRng::seed(0x5EED))
+}
+
+#[test]
+fn fixed_count() {
+    let numbers = generate_many("(1..6:int){3}");
+    assert_eq!(numbers.len(), 3);
+    assert!(numbers.iter().all(|number| *number >= 1.into() && *number <= 6.into() && number.fract().is_zero()));
+    assert_eq!(generate_many("(1..6){0}"), Vec::<Decimal>::new());
+    // Expressions without a quantifier generate a single number
+    assert_eq!(generate_many("5"), vec![Decimal::from(5)]);
+}
+
+#[test]
+fn count_between_bounds() {
+    let gex = grand::compile_raw("(0..1){2,5}").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let mut lengths: Vec<usize> = (0..1000).map(|_| gex.generate_many_with(&mut rng).len()).collect();
+    lengths.sort();
+    lengths.dedup();
+    assert_eq!(lengths, vec![2, 3, 4, 5]);
+}
+
+#[test]
+fn counts_are_integers() {
+    let gex = grand::compile_raw("(0..1){1..3}").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        assert!((1..=3).contains(&gex.generate_many_with(&mut rng).len()));
+    }
+}
+
+#[test]
+fn quantifiers_apply_to_everything_before_them() {
+    let numbers = generate_many("1..6:int{4}");
+    assert_eq!(numbers.len(), 4);
+    // The precision after the quantifier applies to every number
+    assert!(generate_many("(1..6){3}@2").iter().all(|number| number.scale() == 2));
+    // Bindings are generated once for the whole list
+    let numbers = generate_many("$a = 0..10; $a{5}");
+    assert!(numbers.iter().all(|number| *number == numbers[0]));
+}
+
+#[test]
+fn params_as_counts() {
+    let options = CompileOptions {
+        params: vec![ParamDeclaration::bounded("n", 0.into(), 10.into()), ParamDeclaration::new("unbounded")],
+        ..Default::default()
+    };
+    let gex = grand::compile_raw_with("(1..6){n}", &options).unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let numbers = gex.try_generate_many_with_rng_and_params(&mut rng, &Params::new().with("n", 4)).unwrap();
+    assert_eq!(numbers.len(), 4);
+    assert!(matches!(
+        gex.try_generate_many_with_rng_and_params(&mut rng, &Params::new().with("n", -1)),
+        Err(GenerateError::InvalidCount(_))
+    ));
+    // The amount of repetitions needs bounds
+    assert!(grand::compile_raw_with("(1..6){unbounded}", &options).is_err());
+}
+
+#[test]
+fn lists_are_not_numbers() {
+    let gex = grand::compile_raw("(1..6){3}").unwrap();
+    assert!(matches!(gex.try_generate(), Err(GenerateError::MultipleValues)));
+}
+
+#[test]
+fn invalid_quantifiers() {
+    for expression in ["((1..6){3})", "[(1..6){3}]", "$a = (0..1){2}; $a", "(1..6){3}{2}", "(1..6){-1}", "(1..6){5,2}", "(1..6){}", "(1..6){3", "{3}", "(1..6){2000000}"] {
+        assert!(grand::compile_raw(expression).is_err(), "{expression}");
+    }
+}
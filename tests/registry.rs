@@ -0,0 +1,126 @@
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+use grand::{CompileOptions, CompilerError, DiagnosticKind, Params, ParamDeclaration, Registry, SeededRng};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+fn options(functions: Registry) -> CompileOptions {
+    CompileOptions { functions, ..Default::default() }
+}
+
+#[test]
+fn registered_functions_are_called() {
+    let mut functions = Registry::new();
+    functions.register("price", 1, |args: &[Decimal]| args[0].round_dp(2));
+    let gex = grand::compile_raw_with("price(10..20)", &options(functions)).unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    for _ in 0..100 {
+        let number = gex.generate_with(&mut rng);
+        assert!(number >= 10.into() && number <= 20.into());
+        assert_eq!(number.round_dp(2), number);
+    }
+}
+
+#[test]
+fn declared_bounds() {
+    let mut functions = Registry::new();
+    functions.register("any", 1, |args: &[Decimal]| args[0]);
+    functions.register_bounded("percent", 1, 0.into(), 100.into(), |args: &[Decimal]| args[0].max(Decimal::ZERO).min(100.into()));
+    let options = options(functions);
+
+    let gex = grand::compile_raw_with("percent(-50..150)", &options).unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), 100.into()));
+    let gex = grand::compile_raw_with("any(1)", &options).unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (Decimal::MIN, Decimal::MAX));
+    // The bounds of the call are used by the expressions around it
+    let gex = grand::compile_raw_with("percent(1) + 1", &options).unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 101.into()));
+}
+
+#[test]
+fn any_amount_of_arguments() {
+    let mut functions = Registry::new();
+    functions.register("count", 0.., |args: &[Decimal]| args.len().into());
+    let options = options(functions);
+    assert_eq!(grand::compile_raw_with("count()", &options).unwrap().generate(), 0.into());
+    assert_eq!(grand::compile_raw_with("count(1, 2..3, [4, 5])", &options).unwrap().generate(), 3.into());
+    // Arguments are single numbers
+    assert!(grand::compile_raw_with("count((1..6){3})", &options).is_err());
+}
+
+#[test]
+fn wrong_amount_of_arguments() {
+    let mut functions = Registry::new();
+    functions.register("price", 1, |args: &[Decimal]| args[0]);
+    functions.register("between", 2..=3, |args: &[Decimal]| args[1]);
+    let options = options(functions);
+    for (expression, count) in [("price()", 0), ("price(1, 2)", 2), ("between(1)", 1), ("between(1, 2, 3, 4)", 4)] {
+        let diagnostics = grand::compile_raw_with(expression, &options).unwrap_err();
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert!(matches!(diagnostic.kind(), DiagnosticKind::Error(CompilerError::WrongArgumentCount(_, c, 0, 1)) if *c == count), "{expression}");
+        assert_eq!((diagnostic.start(), diagnostic.end()), (0, expression.len()), "{expression}");
+    }
+    assert!(grand::compile_raw_with("between(1, 2)", &options).is_ok());
+    assert!(grand::compile_raw_with("between(1, 2, 3)", &options).is_ok());
+}
+
+#[test]
+fn called_once_per_number() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let mut functions = Registry::new();
+    functions.register("tick", 1, move |_: &[Decimal]| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Decimal::ONE
+    });
+    let gex = grand::compile_raw_with("tick(0..1) + tick(2)", &options(functions)).unwrap();
+    for _ in 0..10 {
+        gex.generate();
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 20);
+}
+
+#[test]
+fn registered_functions_replace_built_ins() {
+    let mut functions = Registry::new();
+    functions.register("max", 0.., |_: &[Decimal]| 42.into());
+    functions.register("abs", 0.., |_: &[Decimal]| 7.into());
+    let options = options(functions);
+    assert_eq!(grand::compile_raw_with("max(1, 2)", &options).unwrap().generate(), 42.into());
+    assert_eq!(grand::compile_raw_with("abs(-1)", &options).unwrap().generate(), 7.into());
+    // Other options keep the built-in functions
+    assert_eq!(grand::compile_raw("max(1, 2)").unwrap().generate(), 2.into());
+}
+
+#[test]
+fn unknown_functions() {
+    assert!(grand::compile_raw("price(10..20)").is_err());
+    let mut functions = Registry::new();
+    functions.register("price", 1, |args: &[Decimal]| args[0]);
+    assert!(grand::compile_raw_with("cost(10..20)", &options(functions)).is_err());
+}
+
+#[test]
+fn with_params() {
+    let mut functions = Registry::new();
+    functions.register("double", 1, |args: &[Decimal]| args[0] * Decimal::TWO);
+    let options = CompileOptions { params: vec![ParamDeclaration::new("level")], ..options(functions) };
+    let gex = grand::compile_raw_with("double({level})", &options).unwrap();
+    assert_eq!(gex.generate_with_params(&Params::new().with("level", 4)), 8.into());
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn compiled_expressions_are_thread_safe() {
+    assert_send_sync::<grand::Gex>();
+    assert_send_sync::<Registry>();
+
+    let mut functions = Registry::new();
+    functions.register("double", 1, |args: &[Decimal]| args[0] * Decimal::TWO);
+    let gex = grand::compile_raw_with("double(1..5:int)", &options(functions)).unwrap().filter(|number| *number > 2.into());
+    let numbers = std::thread::scope(|scope| {
+        let handle = scope.spawn(|| gex.generate_with(&mut SeededRng::seed(0x5EED)));
+        handle.join().unwrap()
+    });
+    assert!((4..=10).contains(&numbers.to_i32().unwrap()));
+}
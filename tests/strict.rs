@@ -0,0 +1,35 @@
+use grand::{CompilerError, DiagnosticKind};
+
+#[test]
+fn unknown_characters() {
+    let gex = grand::compile_raw("0..1O").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), 1.into()));
+
+    let diagnostics = grand::compile_raw_strict("0..1O").unwrap_err();
+    let errors: Vec<_> = diagnostics.iter().map(|diagnostic| (diagnostic.kind(), diagnostic.start(), diagnostic.end())).collect();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], (DiagnosticKind::Error(CompilerError::UnknownCharacter('O', 0, 5)), 4, 5)));
+    assert!(grand::compile_strict("0..1O").is_err());
+}
+
+#[test]
+fn comments_are_allowed_in_strict_mode() {
+    for source in ["# O\n1..2", "1..2 # O", "1 /* O\n * O */ + (0..1)", "(3..4) /* minus / * two */ * 1 - 2"] {
+        let gex = grand::compile_raw_strict(source).unwrap();
+        assert_eq!((gex.min_number(), gex.max_number()), (1.into(), 2.into()), "{source:?}");
+    }
+}
+
+#[test]
+fn comments_and_division() {
+    // `/ *` inside a comment doesn't end it, only `*/` does
+    let gex = grand::compile_raw("(4..8) /* half / * of it */ / 2").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (2.into(), 4.into()));
+    let gex = grand::compile_raw("(0..10)/(1..2)").unwrap();
+    assert_eq!((gex.min_number(), gex.max_number()), (0.into(), 10.into()));
+
+    let diagnostics = grand::compile_raw_strict("1..2 /* open").unwrap_err();
+    let diagnostic = diagnostics.iter().next().unwrap();
+    assert!(matches!(diagnostic.kind(), DiagnosticKind::Error(CompilerError::UnterminatedComment(0, 6))));
+    assert_eq!((diagnostic.start(), diagnostic.end()), (5, 12));
+}
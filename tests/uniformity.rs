@@ -0,0 +1,37 @@
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{chi_squared, count, uniform};
+
+#[test]
+fn selection_is_uniform() {
+    let samples = 60000;
+    let counts = count("[1, 2, 3, 4, 5, 6]", samples);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, (1..=6).map(Decimal::from).collect::<Vec<Decimal>>());
+    // 5 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &uniform(counts.len()), samples) < 20.52);
+}
+
+#[test]
+fn single_entry_selection() {
+    assert_eq!(count("[7]", 100), vec![(Decimal::from(7), 100)]);
+}
+
+#[test]
+fn precalculated_range_includes_both_bounds() {
+    let samples = 50000;
+    let counts = count("0..9|*1|!*10", samples);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, (1..=9).map(Decimal::from).collect::<Vec<Decimal>>());
+    // 8 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &uniform(counts.len()), samples) < 26.12);
+}
+
+#[test]
+fn precalculated_open_range_excludes_bounds() {
+    let counts = count("0,,10|*2|!*3", 10000);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, [2, 4, 8].map(Decimal::from).to_vec());
+}
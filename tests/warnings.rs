@@ -0,0 +1,64 @@
+use std::{cell::RefCell, rc::Rc};
+
+use grand::{CompileOptions, CompilerWarning, Diagnostic, DiagnosticKind, Severity};
+
+/* Options that store the warnings sent to on_warning */
+fn recording(options: CompileOptions) -> (CompileOptions, Rc<RefCell<Vec<Diagnostic>>>) {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = received.clone();
+    let options = CompileOptions { on_warning: Some(Rc::new(move |warning: &Diagnostic| sink.borrow_mut().push(warning.clone()))), ..options };
+    (options, received)
+}
+
+/* Checks that the only warning is returned and sent to on_warning with the same span */
+fn single_warning(source: &str, options: CompileOptions) -> Diagnostic {
+    let (options, received) = recording(options);
+    let warnings = grand::compile_with(source, &options).unwrap().warnings();
+    assert_eq!(warnings.len(), 1, "{source}");
+    assert_eq!(warnings[0].severity(), Severity::Warning);
+
+    let received = received.borrow();
+    assert_eq!(received.len(), 1, "{source}");
+    assert_eq!(received[0].span(), warnings[0].span());
+    assert_eq!(received[0].message(), warnings[0].message());
+    warnings[0].clone()
+}
+
+#[test]
+fn precalculation_skipped() {
+    let warning = single_warning("0..1000|*1|!*2", CompileOptions { precalc_memory_budget: 0, ..Default::default() });
+    assert!(matches!(warning.kind(), DiagnosticKind::Warning(CompilerWarning::PrecalculationSkipped(_, 0, 0, 8))));
+    assert_eq!((warning.start(), warning.end()), (7, 14));
+}
+
+#[test]
+fn rerolled_constraint() {
+    let warning = single_warning("0..10|!*2", CompileOptions { max_rerolls: 50, ..Default::default() });
+    assert!(matches!(warning.kind(), DiagnosticKind::Warning(CompilerWarning::RerolledConstraint(50, 0, 6))));
+    assert_eq!((warning.start(), warning.end()), (5, 9));
+}
+
+#[test]
+fn unused_binding() {
+    let warning = single_warning("$a = 1; 2", CompileOptions::default());
+    assert!(matches!(warning.kind(), DiagnosticKind::Warning(CompilerWarning::UnusedBinding(name, 0, 1)) if name == "$a"));
+    assert_eq!((warning.start(), warning.end()), (0, 2));
+}
+
+#[test]
+fn raw_compilation_sends_warnings() {
+    let (options, received) = recording(CompileOptions::default());
+    grand::compile_raw_with("$a = 1; $b = 2; 0..10|!*2", &options).unwrap();
+    assert_eq!(received.borrow().len(), 3);
+}
+
+#[test]
+fn failed_compilation_returns_warnings() {
+    // Warnings are part of the diagnostics instead of being sent to on_warning
+    let (options, received) = recording(CompileOptions::default());
+    let diagnostics = grand::compile_raw_with("$a = 1; 0..10|!*2 +", &options).unwrap_err();
+    assert!(received.borrow().is_empty());
+    assert_eq!(diagnostics.iter().filter(|diagnostic| diagnostic.severity() == Severity::Warning).count(), 2);
+    assert!(diagnostics.has_errors());
+    assert!(diagnostics.to_string().contains("warning: Unused binding in line 0, column 1"));
+}
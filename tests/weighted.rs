@@ -0,0 +1,82 @@
+use grand::SeededRng;
+use rust_decimal::Decimal;
+
+mod common;
+
+use common::{chi_squared, count};
+
+#[test]
+fn weights_are_followed() {
+    let samples = 100000;
+    let counts = count("[1:70, 2:25, 3:5]", samples);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, (1..=3).map(Decimal::from).collect::<Vec<Decimal>>());
+    // 2 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &[0.7, 0.25, 0.05], samples) < 13.82);
+}
+
+#[test]
+fn decimal_and_default_weights() {
+    let samples = 100000;
+    let counts = count("[1:0.5, 2:.25, 3]", samples);
+    // 2 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &[0.5 / 1.75, 0.25 / 1.75, 1.0 / 1.75], samples) < 13.82);
+}
+
+#[test]
+fn zero_weights_are_never_picked() {
+    let counts = count("[1:0, 2:1, 3:0]", 1000);
+    assert_eq!(counts, vec![(Decimal::from(2), 1000)]);
+
+    let gex = grand::compile_raw("[1:0, 50..60|*2:1]").unwrap();
+    assert_eq!(gex.min_number(), 50.into());
+    assert_eq!(gex.max_number(), 60.into());
+}
+
+#[test]
+fn invalid_weights() {
+    assert!(grand::compile_raw("[1:-5, 2]").is_err());
+    assert!(grand::compile_raw("[1:0, 2:0]").is_err());
+    // Weights are only allowed in selections
+    assert!(grand::compile_raw("1:5").is_err());
+    // Too big to be compared exactly with the other weight
+    assert!(grand::compile_raw("[1:0.0000000000000000000000000001, 2:79228162514264337593543950335]").is_err());
+}
+
+#[test]
+fn weights_after_ranges_and_constraints() {
+    let gex = grand::compile_raw("[0..10|*2:3, 0..:1]").unwrap();
+    assert_eq!(gex.min_number(), 0.into());
+    assert!(grand::compile_raw("[(0..10):3, [1, 2]:1]").is_ok());
+}
+
+#[test]
+fn uniform_counts_values() {
+    let samples = 60000;
+    let counts = count("[1, 2..5:int, (10..20|*5)]|uniform", samples);
+    let values: Vec<Decimal> = counts.iter().map(|(value, _)| *value).collect();
+    assert_eq!(values, [1, 2, 3, 4, 5, 10, 15, 20].map(Decimal::from));
+    // 7 degrees of freedom, p = 0.001
+    assert!(chi_squared(&counts, &[0.125; 8], samples) < 24.32);
+}
+
+#[test]
+fn uniform_uses_lengths_of_ranges() {
+    let samples = 99100;
+    let gex = grand::compile_raw("[0..1, 10..1000]|uniform").unwrap();
+    let mut rng = SeededRng::seed(0x5EED);
+    let small = (0..samples).filter(|_| gex.generate_with(&mut rng) <= Decimal::ONE).count();
+    // 100 expected, about 5 standard deviations of margin
+    assert!((50..150).contains(&small), "{small}");
+
+    // Single numbers have no length next to a range
+    let counts = count("[0..1, 5]|uniform", 1000);
+    assert!(counts.iter().all(|(value, _)| *value <= Decimal::ONE));
+}
+
+#[test]
+fn invalid_modifiers() {
+    assert!(grand::compile_raw("[1:2, 3]|uniform").is_err());
+    assert!(grand::compile_raw("[1, 2]|random").is_err());
+    assert!(grand::compile_raw("0..10|uniform").is_err());
+}